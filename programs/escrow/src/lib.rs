@@ -2,8 +2,19 @@ use anchor_lang::prelude::*;
 use anchor_lang::system_program::{transfer, Transfer};
 use std::collections::BTreeSet;
 
+pub mod pda;
+
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+/// Bump when the `export_escrow` byte layout changes.
+pub const EXPORT_FORMAT_VERSION: u8 = 1;
+/// version + escrow/funder/recipient + status + current_milestone + funded/released/deadline + count
+const EXPORT_HEADER_LEN: usize = 1 + 32 * 3 + 1 + 1 + 8 * 3 + 1;
+/// Per milestone: amount + payout_bps + approval status
+const EXPORT_MILESTONE_LEN: usize = 8 + 2 + 1;
+/// Keeps `escrows_status_batch` output (43 bytes per entry) within the 1024-byte return data limit.
+pub const MAX_STATUS_BATCH: usize = 20;
+
 // ── Events ──────────────────────────────────────────────────────
 
 #[event]
@@ -30,6 +41,19 @@ pub struct MilestoneDisputed {
     pub disputer: Pubkey,
 }
 
+#[event]
+pub struct DisputeResolved {
+    pub escrow: Pubkey,
+    pub milestone_idx: u8,
+    pub disputer: Pubkey,
+    pub resolver: Pubkey,
+    /// True when the rejection stands and the funder is refunded.
+    pub upheld: bool,
+    pub refunded: u64,
+    /// True when applied by `resolve_dispute_by_timeout` rather than the funder.
+    pub by_timeout: bool,
+}
+
 #[event]
 pub struct MilestoneFundsReleased {
     pub escrow: Pubkey,
@@ -38,16 +62,138 @@ pub struct MilestoneFundsReleased {
     pub recipient: Pubkey,
 }
 
+#[event]
+pub struct ScheduledFundingClaimed {
+    pub escrow: Pubkey,
+    pub tranches_claimed: u8,
+    pub amount: u64,
+    pub total_funded: u64,
+}
+
+#[event]
+pub struct ScheduledFundingClosed {
+    pub escrow: Pubkey,
+    pub funder: Pubkey,
+    pub unclaimed: u64,
+}
+
+#[event]
+pub struct EscrowResidualSettled {
+    pub escrow: Pubkey,
+    pub action: OnCompleteAction,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EmergencyRefund {
+    pub escrow: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub approvers_signed: u8,
+}
+
+#[event]
+pub struct MilestoneDocumentAttached {
+    pub escrow: Pubkey,
+    pub milestone_idx: u8,
+    pub doc_index: u8,
+    pub doc_hash: [u8; 32],
+    pub attached_by: Pubkey,
+}
+
+#[event]
+pub struct Donated {
+    pub escrow: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+    pub total_donations: u64,
+}
+
+#[event]
+pub struct DonationsClaimed {
+    pub escrow: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct HoldbackClaimed {
+    pub escrow: Pubkey,
+    pub milestone_idx: u8,
+    pub amount: u64,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct HoldbackReversed {
+    pub escrow: Pubkey,
+    pub milestone_idx: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct StatusMessageUpdated {
+    pub escrow: Pubkey,
+    pub author: Pubkey,
+    pub message: String,
+}
+
+#[event]
+pub struct MilestoneSplit {
+    pub escrow: Pubkey,
+    pub milestone_idx: u8,
+    pub new_milestone_idx: u8,
+    pub remaining_amount: u64,
+    pub split_amount: u64,
+}
+
+#[event]
+pub struct EscrowFunded {
+    pub escrow: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub total_funded: u64,
+    /// Escrow lamport balance re-read after the transfer, rent reserve included
+    pub escrow_balance: u64,
+}
+
+#[event]
+pub struct MilestoneFundingUnlocked {
+    pub escrow: Pubkey,
+    pub milestone_idx: u8,
+    pub funding_bps: u16,
+}
+
 // ── Program ─────────────────────────────────────────────────────
 
 #[program]
 pub mod escrow {
     use super::*;
 
-    pub fn initialize_escrow(ctx: Context<InitializeEscrow>, milestones: Vec<Milestone>, deadline: i64) -> Result<()> {
+    /// `zero_amount_ok` permits zero-amount (acknowledgement-only) milestones.
+    pub fn initialize_escrow(
+        ctx: Context<InitializeEscrow>,
+        milestones: Vec<Milestone>,
+        deadline: i64,
+        zero_amount_ok: bool,
+    ) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
         require!(milestones.len() > 0, ErrorCode::NoMilestones);
         require!(milestones.len() <= 10, ErrorCode::TooManyMilestones);
+        let mut payout_bps_total = 0u32;
+        for m in &milestones {
+            require!(m.amount > 0 || m.payout_bps > 0 || zero_amount_ok, ErrorCode::InvalidMilestoneAmount);
+            m.validate_installments()?;
+            m.validate_payout_bps()?;
+            require!(m.funding_unlock_bps <= 10_000, ErrorCode::InvalidFundingUnlock);
+            payout_bps_total += m.payout_bps as u32;
+        }
+        require!(payout_bps_total <= 10_000, ErrorCode::InvalidPayoutBps);
+        // Percentages summing to 100% leave nothing to back fixed milestones
+        require!(
+            payout_bps_total < 10_000 || milestones.iter().all(|m| m.amount == 0),
+            ErrorCode::InvalidPayoutBps
+        );
         escrow.funder = ctx.accounts.funder.key();
         escrow.recipient = ctx.accounts.recipient.key();
         escrow.milestones = milestones;
@@ -56,8 +202,55 @@ pub mod escrow {
         escrow.total_released = 0;
         escrow.status = Status::Initialized;
         escrow.deadline = deadline;
+        escrow.created_at = Clock::get()?.unix_timestamp;
         escrow.bump = ctx.bumps.escrow;
         escrow.has_multi_approval = false;
+        escrow.on_complete_action = OnCompleteAction::Hold;
+        escrow.donations = 0;
+        escrow.release_holdback_seconds = 0;
+        escrow.status_message = String::new();
+        escrow.first_release_cliff = 0;
+        escrow.first_funded_at = 0;
+        escrow.dispute_timeout = 0;
+        escrow.dispute_default = DisputeOutcome::FavorFunder;
+        escrow.released_bitset = 0;
+        escrow.donations_claimed = 0;
+        Ok(())
+    }
+
+    /// Funder sets a clawback window; releases then park funds in a `PayeeHoldback` PDA.
+    /// Holdback escrows must release through `release_milestone_funds`, so they need
+    /// multi-approval and cannot use installment milestones.
+    pub fn set_release_holdback(ctx: Context<ConfigureEscrow>, release_holdback_seconds: i64) -> Result<()> {
+        require!(release_holdback_seconds >= 0, ErrorCode::InvalidHoldback);
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.release_holdback_seconds = release_holdback_seconds;
+        escrow.validate_holdback()
+    }
+
+    /// Funder locks the first milestone payout until `cliff` seconds after first funding.
+    pub fn set_first_release_cliff(ctx: Context<ConfigureEscrow>, cliff: i64) -> Result<()> {
+        require!(cliff >= 0, ErrorCode::InvalidCliff);
+        ctx.accounts.escrow.first_release_cliff = cliff;
+        Ok(())
+    }
+
+    /// Funder sets how long disputes may stay open and which outcome applies afterwards.
+    pub fn set_dispute_timeout(
+        ctx: Context<ConfigureEscrow>,
+        dispute_timeout: i64,
+        default_outcome: DisputeOutcome,
+    ) -> Result<()> {
+        require!(dispute_timeout >= 0, ErrorCode::InvalidDisputeTimeout);
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.dispute_timeout = dispute_timeout;
+        escrow.dispute_default = default_outcome;
+        Ok(())
+    }
+
+    /// Funder chooses what happens to residual escrow funds once every milestone is paid.
+    pub fn set_on_complete_action(ctx: Context<ConfigureEscrow>, action: OnCompleteAction) -> Result<()> {
+        ctx.accounts.escrow.on_complete_action = action;
         Ok(())
     }
 
@@ -87,18 +280,215 @@ pub mod escrow {
     }
 
     pub fn fund_escrow(ctx: Context<FundEscrow>, amount: u64) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
-        require!(escrow.status == Status::Initialized, ErrorCode::InvalidStatus);
+        require!(ctx.accounts.escrow.status == Status::Initialized, ErrorCode::InvalidStatus);
         require!(amount > 0, ErrorCode::InvalidAmount);
+        ctx.accounts.escrow.validate_holdback()?;
+        // Inbound: the funder is a system account, so a plain System Program transfer works
         let cpi_accounts = Transfer {
             from: ctx.accounts.funder.to_account_info(),
             to: ctx.accounts.escrow.to_account_info(),
         };
         let cpi_program = ctx.accounts.system_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_signer(&[&ctx.accounts.escrow_seeds()]);
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let balance_before = ctx.accounts.escrow.to_account_info().lamports();
         transfer(cpi_ctx, amount)?;
+        let escrow_balance = ctx.accounts.escrow.to_account_info().lamports();
+        require!(
+            escrow_balance >= balance_before.checked_add(amount).ok_or(ErrorCode::Overflow)?,
+            ErrorCode::FundingShortfall
+        );
+        let escrow = &mut ctx.accounts.escrow;
         escrow.total_funded = escrow.total_funded.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        escrow.mark_funded(Clock::get()?.unix_timestamp);
         escrow.status = Status::Funded;
+
+        emit!(EscrowFunded {
+            escrow: escrow.key(),
+            funder: ctx.accounts.funder.key(),
+            amount,
+            total_funded: escrow.total_funded,
+            escrow_balance,
+        });
+
+        Ok(())
+    }
+
+    /// Funder commits a tranche schedule up front. The full amount is held in the
+    /// schedule PDA and moves into the escrow as each tranche unlocks.
+    pub fn create_funding_schedule(
+        ctx: Context<CreateFundingSchedule>,
+        tranches: Vec<FundingTranche>,
+    ) -> Result<()> {
+        require!(!tranches.is_empty() && tranches.len() <= 10, ErrorCode::InvalidSchedule);
+        let mut total = 0u64;
+        let mut prev_unlock = i64::MIN;
+        for t in &tranches {
+            require!(t.amount > 0, ErrorCode::InvalidAmount);
+            require!(t.unlock_time >= prev_unlock, ErrorCode::InvalidSchedule);
+            prev_unlock = t.unlock_time;
+            total = total.checked_add(t.amount).ok_or(ErrorCode::Overflow)?;
+        }
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.funder.to_account_info(),
+            to: ctx.accounts.scheduled_funding.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, total)?;
+
+        let schedule = &mut ctx.accounts.scheduled_funding;
+        schedule.escrow = ctx.accounts.escrow.key();
+        schedule.funder = ctx.accounts.funder.key();
+        schedule.tranches = tranches;
+        schedule.tranches_claimed = 0;
+        schedule.bump = ctx.bumps.scheduled_funding;
+        Ok(())
+    }
+
+    /// Permissionless crank: moves every tranche whose unlock time has passed into the escrow.
+    pub fn claim_scheduled_funding(ctx: Context<ClaimScheduledFunding>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let schedule = &mut ctx.accounts.scheduled_funding;
+        let escrow = &mut ctx.accounts.escrow;
+
+        let mut amount = 0u64;
+        let mut next = schedule.tranches_claimed as usize;
+        while next < schedule.tranches.len() && schedule.tranches[next].unlock_time <= now {
+            amount = amount.checked_add(schedule.tranches[next].amount).ok_or(ErrorCode::Overflow)?;
+            next += 1;
+        }
+        require!(amount > 0, ErrorCode::NoTrancheDue);
+        escrow.validate_holdback()?;
+
+        let schedule_info = schedule.to_account_info();
+        let escrow_info = escrow.to_account_info();
+        move_lamports(&schedule_info, &escrow_info, amount)?;
+
+        schedule.tranches_claimed = next as u8;
+        escrow.total_funded = escrow.total_funded.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        escrow.mark_funded(now);
+        if escrow.status == Status::Initialized {
+            escrow.status = Status::Funded;
+        }
+
+        emit!(ScheduledFundingClaimed {
+            escrow: escrow.key(),
+            tranches_claimed: schedule.tranches_claimed,
+            amount,
+            total_funded: escrow.total_funded,
+        });
+
+        Ok(())
+    }
+
+    /// Funder closes the schedule PDA once the escrow is cancelled or completed,
+    /// reclaiming any tranches that were never claimed along with the rent.
+    pub fn close_funding_schedule(ctx: Context<CloseFundingSchedule>) -> Result<()> {
+        let schedule = &ctx.accounts.scheduled_funding;
+        let unclaimed = schedule.tranches[schedule.tranches_claimed as usize..]
+            .iter()
+            .try_fold(0u64, |acc, t| acc.checked_add(t.amount))
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ScheduledFundingClosed {
+            escrow: ctx.accounts.escrow.key(),
+            funder: ctx.accounts.funder.key(),
+            unclaimed,
+        });
+
+        Ok(())
+    }
+
+    /// Anyone can donate SOL to an open escrow. Donations never count toward refunds;
+    /// whatever releases leave unspent goes to the recipient via `claim_donations`.
+    pub fn donate(ctx: Context<Donate>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.donor.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.donations = escrow.donations.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+        emit!(Donated {
+            escrow: escrow.key(),
+            donor: ctx.accounts.donor.key(),
+            amount,
+            total_donations: escrow.donations,
+        });
+
+        Ok(())
+    }
+
+    /// Recipient withdraws donations not consumed by releases once the escrow is
+    /// cancelled or completed. Funding left for the funder stays in the escrow.
+    pub fn claim_donations(ctx: Context<ClaimDonations>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let escrow_info = escrow.to_account_info();
+        let amount = escrow
+            .unspent_donations()
+            .min(spendable_lamports(&escrow_info)?);
+        require!(amount > 0, ErrorCode::NothingToRelease);
+
+        let recipient_info = ctx.accounts.recipient.to_account_info();
+        move_lamports(&escrow_info, &recipient_info, amount)?;
+        escrow.donations_claimed = escrow.donations_claimed.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+        emit!(DonationsClaimed {
+            escrow: escrow.key(),
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Funder and recipient jointly split an unapproved milestone, appending the
+    /// carved-off `split_amount` as a new milestone at the next free index.
+    pub fn split_milestone(
+        ctx: Context<SplitMilestone>,
+        milestone_idx: u8,
+        split_amount: u64,
+        description: Option<String>,
+    ) -> Result<()> {
+        let approval_info = ctx.accounts.milestone_approval.to_account_info();
+        if !approval_info.data_is_empty() {
+            require_keys_eq!(*approval_info.owner, crate::ID, ErrorCode::InvalidApprovalAccount);
+            let approval = MilestoneApproval::try_deserialize(&mut &approval_info.try_borrow_data()?[..])?;
+            require!(approval.status == MilestoneStatus::Pending, ErrorCode::MilestoneAlreadyFinalized);
+        }
+
+        let escrow = &mut ctx.accounts.escrow;
+        let idx = milestone_idx as usize;
+        require!(idx < escrow.milestones.len(), ErrorCode::InvalidIndex);
+        require!(milestone_idx >= escrow.current_milestone, ErrorCode::MilestoneAlreadyFinalized);
+        require!(escrow.milestones.len() < 10, ErrorCode::TooManyMilestones);
+
+        let original = &mut escrow.milestones[idx];
+        require!(original.installments.is_empty() && original.payout_bps == 0, ErrorCode::CannotSplitMilestone);
+        require!(split_amount > 0 && split_amount < original.amount, ErrorCode::InvalidAmount);
+        original.amount -= split_amount;
+
+        escrow.milestones.push(Milestone {
+            amount: split_amount,
+            description,
+            installments: Vec::new(),
+            installments_paid: 0,
+            payout_bps: 0,
+            funding_unlock_bps: 0,
+        });
+
+        emit!(MilestoneSplit {
+            escrow: escrow.key(),
+            milestone_idx,
+            new_milestone_idx: (escrow.milestones.len() - 1) as u8,
+            remaining_amount: escrow.milestones[idx].amount,
+            split_amount,
+        });
+
         Ok(())
     }
 
@@ -109,7 +499,7 @@ pub mod escrow {
         require!(escrow.status == Status::Funded || escrow.status == Status::Active, ErrorCode::InvalidStatus);
         require!(milestone_idx as usize == escrow.current_milestone as usize, ErrorCode::InvalidIndex);
         require!(milestone_idx as usize < escrow.milestones.len(), ErrorCode::InvalidIndex);
-        escrow.current_milestone += 1;
+        escrow.current_milestone = escrow.current_milestone.checked_add(1).ok_or(ErrorCode::InvalidIndex)?;
         if escrow.current_milestone as usize == escrow.milestones.len() {
             escrow.status = Status::Completed;
         } else {
@@ -145,6 +535,7 @@ pub mod escrow {
         if approval.approvals.is_empty() {
             approval.escrow = escrow.key();
             approval.milestone_idx = milestone_idx;
+            approval.bump = ctx.bumps.milestone_approval;
         }
 
         // Record approval
@@ -166,7 +557,7 @@ pub mod escrow {
         // Check if threshold met
         if threshold_met {
             approval.status = MilestoneStatus::Approved;
-            escrow.current_milestone += 1;
+            escrow.current_milestone = escrow.current_milestone.checked_add(1).ok_or(ErrorCode::InvalidIndex)?;
             if escrow.current_milestone as usize == escrow.milestones.len() {
                 escrow.status = Status::Completed;
             } else {
@@ -177,6 +568,87 @@ pub mod escrow {
         Ok(())
     }
 
+    /// Permissionless crank: approves the current milestone once funding reaches its
+    /// `funding_unlock_bps` threshold, without waiting on approvers.
+    pub fn unlock_funded_milestone(ctx: Context<UnlockFundedMilestone>, milestone_idx: u8) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let approval = &mut ctx.accounts.milestone_approval;
+        require!(milestone_idx as usize == escrow.current_milestone as usize, ErrorCode::InvalidIndex);
+        require!(approval.status == MilestoneStatus::Pending, ErrorCode::MilestoneAlreadyFinalized);
+
+        let threshold = escrow.milestones[milestone_idx as usize].funding_unlock_bps;
+        let funding_bps = escrow.funding_bps()?;
+        require!(threshold > 0 && funding_bps >= threshold, ErrorCode::FundingThresholdNotMet);
+
+        approval.escrow = escrow.key();
+        approval.milestone_idx = milestone_idx;
+        approval.bump = ctx.bumps.milestone_approval;
+        approval.status = MilestoneStatus::Approved;
+        escrow.current_milestone = escrow.current_milestone.checked_add(1).ok_or(ErrorCode::InvalidIndex)?;
+        if escrow.current_milestone as usize == escrow.milestones.len() {
+            escrow.status = Status::Completed;
+        } else {
+            escrow.status = Status::Active;
+        }
+
+        emit!(MilestoneFundingUnlocked {
+            escrow: escrow.key(),
+            milestone_idx,
+            funding_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Funder or recipient posts a short human-readable status update; empty clears it.
+    pub fn set_status_message(ctx: Context<SetStatusMessage>, message: String) -> Result<()> {
+        require!(message.len() <= 128, ErrorCode::StatusMessageTooLong);
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.status_message = message;
+
+        emit!(StatusMessageUpdated {
+            escrow: escrow.key(),
+            author: ctx.accounts.author.key(),
+            message: escrow.status_message.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Approvers attach inspection reports or other evidence to a milestone.
+    pub fn attach_document(
+        ctx: Context<AttachDocument>,
+        milestone_idx: u8,
+        doc_index: u8,
+        doc_hash: [u8; 32],
+        doc_uri: String,
+    ) -> Result<()> {
+        require!(doc_uri.len() <= 200, ErrorCode::UriTooLong);
+        require!((milestone_idx as usize) < ctx.accounts.escrow.milestones.len(), ErrorCode::InvalidIndex);
+        let approver = ctx.accounts.approver.key();
+        require!(ctx.accounts.milestone_config.approvers.contains(&approver), ErrorCode::NotApprover);
+
+        let doc = &mut ctx.accounts.milestone_document;
+        doc.escrow = ctx.accounts.escrow.key();
+        doc.milestone_idx = milestone_idx;
+        doc.doc_index = doc_index;
+        doc.doc_hash = doc_hash;
+        doc.doc_uri = doc_uri;
+        doc.attached_by = approver;
+        doc.attached_at = Clock::get()?.unix_timestamp;
+        doc.bump = ctx.bumps.milestone_document;
+
+        emit!(MilestoneDocumentAttached {
+            escrow: doc.escrow,
+            milestone_idx,
+            doc_index,
+            doc_hash,
+            attached_by: approver,
+        });
+
+        Ok(())
+    }
+
     /// Any approver can reject a pending milestone.
     pub fn reject_milestone(
         ctx: Context<RejectMilestone>,
@@ -195,6 +667,7 @@ pub mod escrow {
         if approval.approvals.is_empty() {
             approval.escrow = ctx.accounts.escrow.key();
             approval.milestone_idx = milestone_idx;
+            approval.bump = ctx.bumps.milestone_approval;
         }
 
         approval.status = MilestoneStatus::Rejected;
@@ -220,6 +693,8 @@ pub mod escrow {
             ErrorCode::CanOnlyDisputeRejected
         );
         approval.status = MilestoneStatus::Disputed;
+        approval.disputer = ctx.accounts.disputer.key();
+        approval.disputed_at = Clock::get()?.unix_timestamp;
 
         emit!(MilestoneDisputed {
             escrow: ctx.accounts.escrow.key(),
@@ -234,19 +709,82 @@ pub mod escrow {
         let escrow = &mut ctx.accounts.escrow;
         let approval = &mut ctx.accounts.milestone_approval;
         require!(approval.status == MilestoneStatus::Disputed, ErrorCode::NotDisputed);
-        require!(ctx.accounts.resolver.key() == escrow.funder, ErrorCode::UnauthorizedResolve); // Only funder can resolve by refunding
-        let refund_amount = escrow.total_funded.saturating_sub(escrow.total_released);
+        require!(ctx.accounts.funder.key() == escrow.funder, ErrorCode::UnauthorizedResolve); // Only funder can resolve by refunding
+        let escrow_info = escrow.to_account_info();
+        let refund_amount = escrow
+            .total_funded
+            .saturating_sub(escrow.total_released)
+            .min(spendable_lamports(&escrow_info)?);
         if refund_amount > 0 {
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.escrow.to_account_info(),
-                to: ctx.accounts.funder.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.system_program.to_account_info();
-            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_signer(&[&ctx.accounts.escrow_seeds()]);
-            transfer(cpi_ctx, refund_amount)?;
+            let funder_info = ctx.accounts.funder.to_account_info();
+            move_lamports(&escrow_info, &funder_info, refund_amount)?;
         }
         escrow.status = Status::Cancelled;
         approval.status = MilestoneStatus::Resolved;
+
+        // Funder-side resolution always upholds the rejection by refunding
+        emit!(DisputeResolved {
+            escrow: escrow.key(),
+            milestone_idx,
+            disputer: approval.disputer,
+            resolver: ctx.accounts.funder.key(),
+            upheld: true,
+            refunded: refund_amount,
+            by_timeout: false,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: applies the escrow's default outcome to a dispute left open past its timeout.
+    pub fn resolve_dispute_by_timeout(ctx: Context<ResolveDisputeByTimeout>, milestone_idx: u8) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let approval = &mut ctx.accounts.milestone_approval;
+        require!(approval.status == MilestoneStatus::Disputed, ErrorCode::NotDisputed);
+        require!(escrow.dispute_timeout > 0, ErrorCode::DisputeTimeoutDisabled);
+        let expires_at = approval
+            .disputed_at
+            .checked_add(escrow.dispute_timeout)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(Clock::get()?.unix_timestamp >= expires_at, ErrorCode::DisputeNotExpired);
+
+        let mut refunded = 0u64;
+        match escrow.dispute_default {
+            DisputeOutcome::FavorFunder => {
+                let escrow_info = escrow.to_account_info();
+                refunded = escrow
+                    .total_funded
+                    .saturating_sub(escrow.total_released)
+                    .min(spendable_lamports(&escrow_info)?);
+                if refunded > 0 {
+                    let funder_info = ctx.accounts.funder.to_account_info();
+                    move_lamports(&escrow_info, &funder_info, refunded)?;
+                }
+                escrow.status = Status::Cancelled;
+                approval.status = MilestoneStatus::Resolved;
+            }
+            DisputeOutcome::FavorRecipient => {
+                require!(milestone_idx == escrow.current_milestone, ErrorCode::InvalidIndex);
+                approval.status = MilestoneStatus::Approved;
+                escrow.current_milestone = escrow.current_milestone.checked_add(1).ok_or(ErrorCode::InvalidIndex)?;
+                if escrow.current_milestone as usize == escrow.milestones.len() {
+                    escrow.status = Status::Completed;
+                } else {
+                    escrow.status = Status::Active;
+                }
+            }
+        }
+
+        emit!(DisputeResolved {
+            escrow: escrow.key(),
+            milestone_idx,
+            disputer: approval.disputer,
+            resolver: ctx.accounts.cranker.key(),
+            upheld: escrow.dispute_default == DisputeOutcome::FavorFunder,
+            refunded,
+            by_timeout: true,
+        });
+
         Ok(())
     }
 
@@ -260,21 +798,47 @@ pub mod escrow {
 
         require!(approval.status == MilestoneStatus::Approved, ErrorCode::MilestoneNotApproved);
         require!((milestone_idx as usize) < escrow.milestones.len(), ErrorCode::InvalidIndex);
+        require!(!escrow.is_released(milestone_idx), ErrorCode::MilestoneAlreadyReleased);
 
-        let amount = escrow.milestones[milestone_idx as usize].amount;
-        require!(amount > 0, ErrorCode::NothingToRelease);
+        let now = Clock::get()?.unix_timestamp;
+        require!(milestone_idx != 0 || escrow.first_release_cliff_passed(now), ErrorCode::FirstReleaseCliff);
 
-        // Check sufficient funds
-        let escrow_lamports = escrow.to_account_info().lamports();
-        require!(escrow_lamports >= amount, ErrorCode::InsufficientFunds);
+        // Installment milestones pay whatever has come due; plain milestones pay in full
+        let (amount, installments_paid) = escrow.milestones[milestone_idx as usize].due_amount(now, escrow.total_funded)?;
+        require!(amount > 0, ErrorCode::NothingToRelease);
+        let new_released = escrow.checked_release_total(amount)?;
 
-        // Transfer SOL from escrow PDA to recipient via direct lamport manipulation
+        // Check sufficient funds above the rent-exempt minimum
         let escrow_info = escrow.to_account_info();
-        let recipient_info = ctx.accounts.recipient.to_account_info();
-        **escrow_info.try_borrow_mut_lamports()? -= amount;
-        **recipient_info.try_borrow_mut_lamports()? += amount;
+        require!(spendable_lamports(&escrow_info)? >= amount, ErrorCode::InsufficientFunds);
+
+        // Transfer SOL from escrow PDA to recipient (or the holdback PDA)
+        let destination_info = if escrow.release_holdback_seconds > 0 {
+            let holdback = ctx
+                .accounts
+                .payee_holdback
+                .as_mut()
+                .ok_or(ErrorCode::HoldbackAccountRequired)?;
+            holdback.escrow = escrow.key();
+            holdback.milestone_idx = milestone_idx;
+            holdback.amount = holdback.amount.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+            holdback.claimable_at = now
+                .checked_add(escrow.release_holdback_seconds)
+                .ok_or(ErrorCode::Overflow)?;
+            holdback.bump = ctx.bumps.payee_holdback.ok_or(ErrorCode::HoldbackAccountRequired)?;
+            holdback.to_account_info()
+        } else {
+            ctx.accounts.recipient.to_account_info()
+        };
+        move_lamports(&escrow_info, &destination_info, amount)?;
 
-        escrow.total_released = escrow.total_released.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        let milestone = &mut escrow.milestones[milestone_idx as usize];
+        milestone.installments_paid = installments_paid;
+        let fully_paid = milestone.installments.is_empty() || installments_paid as usize == milestone.installments.len();
+        if fully_paid {
+            escrow.mark_released(milestone_idx);
+        }
+        escrow.total_released = new_released;
 
         emit!(MilestoneFundsReleased {
             escrow: escrow.key(),
@@ -283,42 +847,247 @@ pub mod escrow {
             recipient: ctx.accounts.recipient.key(),
         });
 
-        Ok(())
+        settle_residual(escrow, &ctx.accounts.funder.to_account_info())
     }
 
-    pub fn release_funds(ctx: Context<ReleaseFunds>) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
-        require!(escrow.status == Status::Active || escrow.status == Status::Completed, ErrorCode::InvalidStatus);
-        let mut to_release = 0u64;
-        for i in 0..escrow.current_milestone as usize {
-            to_release = to_release.checked_add(escrow.milestones[i].amount).ok_or(ErrorCode::Overflow)?;
-        }
-        require!(to_release > escrow.total_released, ErrorCode::NothingToRelease);
-        let remaining = to_release.saturating_sub(escrow.total_released);
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow.to_account_info(),
-            to: ctx.accounts.recipient.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.system_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_signer(&[&ctx.accounts.escrow_seeds()]);
-        transfer(cpi_ctx, remaining)?;
-        escrow.total_released = escrow.total_released.checked_add(remaining).ok_or(ErrorCode::Overflow)?;
-        Ok(())
-    }
+    /// Recipient collects held-back release funds once the clawback window has passed.
+    pub fn claim_holdback(ctx: Context<ClaimHoldback>, milestone_idx: u8) -> Result<()> {
+        let holdback = &mut ctx.accounts.payee_holdback;
+        require!(Clock::get()?.unix_timestamp >= holdback.claimable_at, ErrorCode::HoldbackLocked);
 
-    pub fn cancel_escrow(ctx: Context<CancelEscrow>) -> Result<()> {
-        let escrow = &mut ctx.accounts.escrow;
+        let amount = holdback.amount;
+        let holdback_info = holdback.to_account_info();
+        let recipient_info = ctx.accounts.recipient.to_account_info();
+        move_lamports(&holdback_info, &recipient_info, amount)?;
+        holdback.amount = 0;
+
+        emit!(HoldbackClaimed {
+            escrow: ctx.accounts.escrow.key(),
+            milestone_idx,
+            amount,
+            recipient: ctx.accounts.recipient.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Funder claws held-back funds straight back while the clawback window is open.
+    /// The milestone stays marked in `released_bitset` so neither release path pays it
+    /// again, even once the escrow is completed or cancelled.
+    pub fn reverse_holdback(ctx: Context<ReverseHoldback>, milestone_idx: u8) -> Result<()> {
+        let holdback = &mut ctx.accounts.payee_holdback;
+        require!(Clock::get()?.unix_timestamp < holdback.claimable_at, ErrorCode::HoldbackWindowClosed);
+
+        let amount = holdback.amount;
+        let holdback_info = holdback.to_account_info();
+        let funder_info = ctx.accounts.funder.to_account_info();
+        move_lamports(&holdback_info, &funder_info, amount)?;
+        holdback.amount = 0;
+
+        // The clawed-back amount is neither released nor still funded
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.total_released = escrow.total_released.checked_sub(amount).ok_or(ErrorCode::Overflow)?;
+        escrow.total_funded = escrow.total_funded.checked_sub(amount).ok_or(ErrorCode::Overflow)?;
+        escrow.mark_released(milestone_idx);
+
+        emit!(HoldbackReversed {
+            escrow: escrow.key(),
+            milestone_idx,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Read-only check of every `release_milestone_funds` precondition for a milestone.
+    /// Returns a `ReleaseBlocker` code (0 = releasable) via return data.
+    pub fn is_releasable(ctx: Context<IsReleasable>, milestone_idx: u8) -> Result<u8> {
+        let escrow = &ctx.accounts.escrow;
+
+        if (milestone_idx as usize) >= escrow.milestones.len() {
+            return Ok(ReleaseBlocker::InvalidIndex as u8);
+        }
+        let status = match &ctx.accounts.milestone_approval {
+            Some(approval) => approval.status.clone(),
+            None => MilestoneStatus::Pending,
+        };
+        match status {
+            MilestoneStatus::Approved => {}
+            MilestoneStatus::Disputed => return Ok(ReleaseBlocker::Disputed as u8),
+            _ => return Ok(ReleaseBlocker::NotApproved as u8),
+        }
+        if escrow.is_released(milestone_idx) {
+            return Ok(ReleaseBlocker::AlreadyReleased as u8);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if milestone_idx == 0 && !escrow.first_release_cliff_passed(now) {
+            return Ok(ReleaseBlocker::CliffNotReached as u8);
+        }
+
+        let (amount, _) = escrow.milestones[milestone_idx as usize].due_amount(now, escrow.total_funded)?;
+        if amount == 0 {
+            return Ok(ReleaseBlocker::NothingToRelease as u8);
+        }
+        if spendable_lamports(&escrow.to_account_info())? < amount {
+            return Ok(ReleaseBlocker::InsufficientFunds as u8);
+        }
+        if escrow.checked_release_total(amount).is_err() {
+            return Ok(ReleaseBlocker::OverRelease as u8);
+        }
+
+        Ok(ReleaseBlocker::Releasable as u8)
+    }
+
+    /// Read-only compact export of the escrow for off-chain archival. Milestone approval
+    /// accounts may be passed via remaining_accounts to include their statuses.
+    pub fn export_escrow<'info>(ctx: Context<'_, '_, 'info, 'info, ExportEscrow<'info>>) -> Result<Vec<u8>> {
+        let escrow = &ctx.accounts.escrow;
+        let escrow_key = escrow.key();
+
+        let mut statuses = vec![MilestoneStatus::Pending; escrow.milestones.len()];
+        for info in ctx.remaining_accounts.iter() {
+            let approval = Account::<MilestoneApproval>::try_from(info)?;
+            require_keys_eq!(approval.escrow, escrow_key, ErrorCode::InvalidApprovalAccount);
+            let idx = approval.milestone_idx as usize;
+            require!(idx < statuses.len(), ErrorCode::InvalidIndex);
+            statuses[idx] = approval.status.clone();
+        }
+
+        let mut out = Vec::with_capacity(EXPORT_HEADER_LEN + escrow.milestones.len() * EXPORT_MILESTONE_LEN);
+        out.push(EXPORT_FORMAT_VERSION);
+        out.extend_from_slice(escrow_key.as_ref());
+        out.extend_from_slice(escrow.funder.as_ref());
+        out.extend_from_slice(escrow.recipient.as_ref());
+        out.push(escrow.status.clone() as u8);
+        out.push(escrow.current_milestone);
+        out.extend_from_slice(&escrow.total_funded.to_le_bytes());
+        out.extend_from_slice(&escrow.total_released.to_le_bytes());
+        out.extend_from_slice(&escrow.deadline.to_le_bytes());
+        out.push(escrow.milestones.len() as u8);
+        for (m, status) in escrow.milestones.iter().zip(statuses) {
+            out.extend_from_slice(&m.amount.to_le_bytes());
+            out.extend_from_slice(&m.payout_bps.to_le_bytes());
+            out.push(status as u8);
+        }
+        Ok(out)
+    }
+
+    /// Read-only: lamports the funder still needs to deposit so every unreleased
+    /// milestone is backed on top of the escrow's rent-exempt minimum.
+    pub fn required_topup(ctx: Context<RequiredTopup>) -> Result<u64> {
+        let escrow = &ctx.accounts.escrow;
+        let escrow_info = escrow.to_account_info();
+        let outstanding = escrow.outstanding_payouts()?;
+        Ok(outstanding.saturating_sub(spendable_lamports(&escrow_info)?))
+    }
+
+    /// Read-only milestone completion and funding progress in basis points.
+    pub fn progress_bps(ctx: Context<ProgressBps>) -> Result<Progress> {
+        let escrow = &ctx.accounts.escrow;
+        Ok(Progress {
+            completion_bps: escrow.completion_bps(),
+            funding_bps: escrow.funding_bps()?,
+        })
+    }
+
+    /// Read-only status summary for up to `MAX_STATUS_BATCH` escrows passed via remaining_accounts.
+    pub fn escrows_status_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EscrowsStatusBatch>,
+    ) -> Result<Vec<EscrowStatusEntry>> {
+        require!(ctx.remaining_accounts.len() <= MAX_STATUS_BATCH, ErrorCode::BatchTooLarge);
+        let mut out = Vec::with_capacity(ctx.remaining_accounts.len());
+        for info in ctx.remaining_accounts.iter() {
+            let escrow = Account::<Escrow>::try_from(info)?;
+            out.push(EscrowStatusEntry {
+                escrow: escrow.key(),
+                total_funded: escrow.total_funded,
+                completion_bps: escrow.completion_bps(),
+                status: escrow.status.clone(),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Pays every approved milestone not yet marked in `released_bitset`, so it never
+    /// overlaps with `release_milestone_funds` for the same milestone.
+    pub fn release_funds(ctx: Context<ReleaseFunds>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.status == Status::Active || escrow.status == Status::Completed, ErrorCode::InvalidStatus);
+        // Holdback payouts need a per-milestone PDA, which only release_milestone_funds takes
+        require!(escrow.release_holdback_seconds == 0, ErrorCode::HoldbackRequiresMilestoneRelease);
+        let now = Clock::get()?.unix_timestamp;
+        require!(escrow.is_released(0) || escrow.first_release_cliff_passed(now), ErrorCode::FirstReleaseCliff);
+        let total_funded = escrow.total_funded;
+        let mut amount = 0u64;
+        for idx in 0..escrow.current_milestone {
+            if escrow.is_released(idx) {
+                continue;
+            }
+            // Installment milestones pay only what has unlocked and stay open until fully paid
+            let milestone = &mut escrow.milestones[idx as usize];
+            let (due, installments_paid) = milestone.due_amount(now, total_funded)?;
+            amount = amount.checked_add(due).ok_or(ErrorCode::Overflow)?;
+            milestone.installments_paid = installments_paid;
+            if milestone.installments.is_empty() || installments_paid as usize == milestone.installments.len() {
+                escrow.mark_released(idx);
+            }
+        }
+        require!(amount > 0, ErrorCode::NothingToRelease);
+        let new_released = escrow.checked_release_total(amount)?;
+        let escrow_info = escrow.to_account_info();
+        let recipient_info = ctx.accounts.recipient.to_account_info();
+        move_lamports(&escrow_info, &recipient_info, amount)?;
+        escrow.total_released = new_released;
+
+        settle_residual(escrow, &ctx.accounts.funder.to_account_info())
+    }
+
+    /// Last-resort refund of everything unreleased, ignoring deadline and milestone state.
+    /// Requires the funder plus every configured approver (via remaining_accounts) to sign.
+    pub fn emergency_refund_all(ctx: Context<EmergencyRefundAll>) -> Result<()> {
+        let config = &ctx.accounts.milestone_config;
+        for approver in &config.approvers {
+            require!(
+                ctx.remaining_accounts.iter().any(|a| a.key == approver && a.is_signer),
+                ErrorCode::MissingApproverSignature
+            );
+        }
+
+        let escrow = &mut ctx.accounts.escrow;
+        let escrow_info = escrow.to_account_info();
+        let refund_amount = escrow
+            .total_funded
+            .saturating_sub(escrow.total_released)
+            .min(spendable_lamports(&escrow_info)?);
+        if refund_amount > 0 {
+            let funder_info = ctx.accounts.funder.to_account_info();
+            move_lamports(&escrow_info, &funder_info, refund_amount)?;
+        }
+        escrow.status = Status::Cancelled;
+
+        emit!(EmergencyRefund {
+            escrow: escrow.key(),
+            funder: ctx.accounts.funder.key(),
+            amount: refund_amount,
+            approvers_signed: config.approvers.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_escrow(ctx: Context<CancelEscrow>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
         require!(escrow.status != Status::Completed, ErrorCode::CannotCancelCompleted);
         require!(Clock::get()?.unix_timestamp < escrow.deadline, ErrorCode::DeadlinePassed);
-        let refund_amount = escrow.total_funded.saturating_sub(escrow.total_released);
+        let escrow_info = escrow.to_account_info();
+        let refund_amount = escrow
+            .total_funded
+            .saturating_sub(escrow.total_released)
+            .min(spendable_lamports(&escrow_info)?);
         if refund_amount > 0 {
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.escrow.to_account_info(),
-                to: ctx.accounts.funder.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.system_program.to_account_info();
-            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_signer(&[&ctx.accounts.escrow_seeds()]);
-            transfer(cpi_ctx, refund_amount)?;
+            let funder_info = ctx.accounts.funder.to_account_info();
+            move_lamports(&escrow_info, &funder_info, refund_amount)?;
         }
         escrow.status = Status::Cancelled;
         Ok(())
@@ -328,15 +1097,14 @@ pub mod escrow {
         let escrow = &mut ctx.accounts.escrow;
         require!(escrow.status != Status::Completed && escrow.status != Status::Cancelled, ErrorCode::InvalidStatus);
         require!(Clock::get()?.unix_timestamp > escrow.deadline, ErrorCode::DeadlineNotPassed);
-        let refund_amount = escrow.total_funded.saturating_sub(escrow.total_released);
+        let escrow_info = escrow.to_account_info();
+        let refund_amount = escrow
+            .total_funded
+            .saturating_sub(escrow.total_released)
+            .min(spendable_lamports(&escrow_info)?);
         if refund_amount > 0 {
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.escrow.to_account_info(),
-                to: ctx.accounts.funder.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.system_program.to_account_info();
-            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_signer(&[&ctx.accounts.escrow_seeds()]);
-            transfer(cpi_ctx, refund_amount)?;
+            let funder_info = ctx.accounts.funder.to_account_info();
+            move_lamports(&escrow_info, &funder_info, refund_amount)?;
         }
         escrow.status = Status::Cancelled;
         Ok(())
@@ -351,8 +1119,8 @@ pub struct InitializeEscrow<'info> {
     #[account(
         init,
         payer = funder,
-        space = 8 + 1024,
-        seeds = [b"escrow", funder.key().as_ref(), recipient.key().as_ref()],
+        space = 8 + 2048,  // milestones incl. installment schedules + status_message
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), recipient.key().as_ref()],
         bump
     )]
     pub escrow: Account<'info, Escrow>,
@@ -365,32 +1133,92 @@ pub struct InitializeEscrow<'info> {
 
 #[derive(Accounts)]
 pub struct FundEscrow<'info> {
-    #[account(mut, seeds = [b"escrow", escrow.funder.as_ref(), escrow.recipient.as_ref()], bump = escrow.bump)]
+    #[account(mut, seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()], bump = escrow.bump)]
     pub escrow: Account<'info, Escrow>,
     #[account(mut)]
     pub funder: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct Donate<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status != Status::Completed && escrow.status != Status::Cancelled @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDonations<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), recipient.key().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status == Status::Completed || escrow.status == Status::Cancelled @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetStatusMessage<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = author.key() == escrow.funder || author.key() == escrow.recipient @ ErrorCode::UnauthorizedStatusMessage,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    /// Funder or recipient
+    pub author: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(milestone_idx: u8)]
+pub struct SplitMilestone<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), recipient.key().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status != Status::Completed && escrow.status != Status::Cancelled @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    /// CHECK: may not exist yet; if it does it must still be Pending (checked in handler)
+    #[account(seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]], bump)]
+    pub milestone_approval: UncheckedAccount<'info>,
+    pub funder: Signer<'info>,
+    pub recipient: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ApproveMilestone<'info> {
-    #[account(mut, seeds = [b"escrow", funder.key().as_ref(), escrow.recipient.as_ref()], bump = escrow.bump)]
+    #[account(mut, seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()], bump = escrow.bump)]
     pub escrow: Account<'info, Escrow>,
     pub funder: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct ReleaseFunds<'info> {
-    #[account(mut, seeds = [b"escrow", escrow.funder.as_ref(), recipient.key().as_ref()], bump = escrow.bump)]
+    #[account(mut, seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), recipient.key().as_ref()], bump = escrow.bump)]
     pub escrow: Account<'info, Escrow>,
     #[account(mut)]
     pub recipient: Signer<'info>,
+    /// Receives residual funds when the escrow completes with `OnCompleteAction::RefundFunder`
+    #[account(mut, address = escrow.funder @ ErrorCode::InvalidFunder)]
+    pub funder: SystemAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct CancelEscrow<'info> {
-    #[account(mut, seeds = [b"escrow", funder.key().as_ref(), escrow.recipient.as_ref()], bump = escrow.bump)]
+    #[account(mut, seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()], bump = escrow.bump)]
     pub escrow: Account<'info, Escrow>,
     #[account(mut)]
     pub funder: Signer<'info>,
@@ -399,7 +1227,7 @@ pub struct CancelEscrow<'info> {
 
 #[derive(Accounts)]
 pub struct RefundAfterDeadline<'info> {
-    #[account(mut, seeds = [b"escrow", funder.key().as_ref(), escrow.recipient.as_ref()], bump = escrow.bump)]
+    #[account(mut, seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()], bump = escrow.bump)]
     pub escrow: Account<'info, Escrow>,
     #[account(mut)]
     pub funder: Signer<'info>,
@@ -408,11 +1236,24 @@ pub struct RefundAfterDeadline<'info> {
 
 // ── New Account Validation Structs (EGRID-003) ──────────────────
 
+/// Funder-only escrow settings, editable until the escrow is funded.
+#[derive(Accounts)]
+pub struct ConfigureEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status == Status::Initialized @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    pub funder: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ConfigureMilestones<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", funder.key().as_ref(), escrow.recipient.as_ref()],
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()],
         bump = escrow.bump,
         constraint = escrow.status == Status::Initialized @ ErrorCode::InvalidStatus,
     )]
@@ -421,7 +1262,7 @@ pub struct ConfigureMilestones<'info> {
         init,
         payer = funder,
         space = 8 + 32 + (4 + 32 * 5) + 1 + 1,  // 206 bytes
-        seeds = [b"milestone_config", escrow.key().as_ref()],
+        seeds = [pda::MILESTONE_CONFIG_SEED, escrow.key().as_ref()],
         bump,
     )]
     pub milestone_config: Account<'info, MilestoneConfig>,
@@ -435,22 +1276,22 @@ pub struct ConfigureMilestones<'info> {
 pub struct ApproveMilestoneMulti<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
         bump = escrow.bump,
         constraint = escrow.has_multi_approval @ ErrorCode::NotMultiApproval,
         constraint = escrow.status == Status::Funded || escrow.status == Status::Active @ ErrorCode::InvalidStatus,
     )]
     pub escrow: Account<'info, Escrow>,
     #[account(
-        seeds = [b"milestone_config", escrow.key().as_ref()],
+        seeds = [pda::MILESTONE_CONFIG_SEED, escrow.key().as_ref()],
         bump = milestone_config.bump,
     )]
     pub milestone_config: Account<'info, MilestoneConfig>,
     #[account(
         init_if_needed,
         payer = approver,
-        space = 8 + 32 + 1 + (4 + (32 + 8) * 5) + 1 + 1,  // 247 bytes
-        seeds = [b"milestone_approval", escrow.key().as_ref(), &[milestone_idx]],
+        space = 8 + 32 + 1 + (4 + (32 + 8) * 5) + 1 + 1 + 32 + 8,  // 287 bytes
+        seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]],
         bump,
     )]
     pub milestone_approval: Account<'info, MilestoneApproval>,
@@ -459,25 +1300,48 @@ pub struct ApproveMilestoneMulti<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(milestone_idx: u8)]
+pub struct UnlockFundedMilestone<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status == Status::Funded || escrow.status == Status::Active @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 1 + (4 + (32 + 8) * 5) + 1 + 1 + 32 + 8,
+        seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]],
+        bump,
+    )]
+    pub milestone_approval: Account<'info, MilestoneApproval>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(milestone_idx: u8)]
 pub struct RejectMilestone<'info> {
     #[account(
-        seeds = [b"escrow", escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
         bump = escrow.bump,
         constraint = escrow.has_multi_approval @ ErrorCode::NotMultiApproval,
     )]
     pub escrow: Account<'info, Escrow>,
     #[account(
-        seeds = [b"milestone_config", escrow.key().as_ref()],
+        seeds = [pda::MILESTONE_CONFIG_SEED, escrow.key().as_ref()],
         bump = milestone_config.bump,
     )]
     pub milestone_config: Account<'info, MilestoneConfig>,
     #[account(
         init_if_needed,
         payer = approver,
-        space = 8 + 32 + 1 + (4 + (32 + 8) * 5) + 1 + 1,
-        seeds = [b"milestone_approval", escrow.key().as_ref(), &[milestone_idx]],
+        space = 8 + 32 + 1 + (4 + (32 + 8) * 5) + 1 + 1 + 32 + 8,
+        seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]],
         bump,
     )]
     pub milestone_approval: Account<'info, MilestoneApproval>,
@@ -486,18 +1350,45 @@ pub struct RejectMilestone<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(milestone_idx: u8, doc_index: u8)]
+pub struct AttachDocument<'info> {
+    #[account(
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.has_multi_approval @ ErrorCode::NotMultiApproval,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        seeds = [pda::MILESTONE_CONFIG_SEED, escrow.key().as_ref()],
+        bump = milestone_config.bump,
+    )]
+    pub milestone_config: Account<'info, MilestoneConfig>,
+    #[account(
+        init,
+        payer = approver,
+        space = 8 + 32 + 1 + 1 + 32 + (4 + 200) + 32 + 8 + 1,  // 319 bytes
+        seeds = [pda::MILESTONE_DOCUMENT_SEED, escrow.key().as_ref(), &[milestone_idx], &[doc_index]],
+        bump,
+    )]
+    pub milestone_document: Account<'info, MilestoneDocument>,
+    #[account(mut)]
+    pub approver: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(milestone_idx: u8)]
 pub struct DisputeMilestone<'info> {
     #[account(
-        seeds = [b"escrow", escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
         bump = escrow.bump,
         constraint = disputer.key() == escrow.funder || disputer.key() == escrow.recipient @ ErrorCode::UnauthorizedDispute,
     )]
     pub escrow: Account<'info, Escrow>,
     #[account(
         mut,
-        seeds = [b"milestone_approval", escrow.key().as_ref(), &[milestone_idx]],
+        seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]],
         bump = milestone_approval.bump,
     )]
     pub milestone_approval: Account<'info, MilestoneApproval>,
@@ -510,13 +1401,13 @@ pub struct DisputeMilestone<'info> {
 pub struct ResolveDispute<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", funder.key().as_ref(), escrow.recipient.as_ref()],
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()],
         bump = escrow.bump,
     )]
     pub escrow: Account<'info, Escrow>,
     #[account(
         mut,
-        seeds = [b"milestone_approval", escrow.key().as_ref(), &[milestone_idx]],
+        seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]],
         bump = milestone_approval.bump,
     )]
     pub milestone_approval: Account<'info, MilestoneApproval>,
@@ -525,27 +1416,230 @@ pub struct ResolveDispute<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(milestone_idx: u8)]
+pub struct ResolveDisputeByTimeout<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status == Status::Funded || escrow.status == Status::Active @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]],
+        bump = milestone_approval.bump,
+    )]
+    pub milestone_approval: Account<'info, MilestoneApproval>,
+    /// Receives the refund when the default outcome favors the funder
+    #[account(mut, address = escrow.funder @ ErrorCode::InvalidFunder)]
+    pub funder: SystemAccount<'info>,
+    pub cranker: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(milestone_idx: u8)]
 pub struct ReleaseMilestoneFunds<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", escrow.funder.as_ref(), recipient.key().as_ref()],
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), recipient.key().as_ref()],
         bump = escrow.bump,
     )]
     pub escrow: Account<'info, Escrow>,
     #[account(
-        seeds = [b"milestone_approval", escrow.key().as_ref(), &[milestone_idx]],
+        seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]],
         bump = milestone_approval.bump,
     )]
     pub milestone_approval: Account<'info, MilestoneApproval>,
     #[account(mut)]
     pub recipient: Signer<'info>,
+    /// Receives residual funds when the escrow completes with `OnCompleteAction::RefundFunder`
+    #[account(mut, address = escrow.funder @ ErrorCode::InvalidFunder)]
+    pub funder: SystemAccount<'info>,
+    /// Required when the escrow has a release holdback window
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        space = 8 + 32 + 1 + 8 + 8 + 1,  // 58 bytes
+        seeds = [pda::PAYEE_HOLDBACK_SEED, escrow.key().as_ref(), &[milestone_idx]],
+        bump,
+    )]
+    pub payee_holdback: Option<Account<'info, PayeeHoldback>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(milestone_idx: u8)]
+pub struct ClaimHoldback<'info> {
+    #[account(
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), recipient.key().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [pda::PAYEE_HOLDBACK_SEED, escrow.key().as_ref(), &[milestone_idx]],
+        bump = payee_holdback.bump,
+    )]
+    pub payee_holdback: Account<'info, PayeeHoldback>,
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(milestone_idx: u8)]
+pub struct ReverseHoldback<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), recipient.key().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [pda::PAYEE_HOLDBACK_SEED, escrow.key().as_ref(), &[milestone_idx]],
+        bump = payee_holdback.bump,
+    )]
+    pub payee_holdback: Account<'info, PayeeHoldback>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    /// CHECK: receives the holdback rent it paid for; checked via the escrow seeds
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(milestone_idx: u8)]
+pub struct IsReleasable<'info> {
+    #[account(
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    /// Absent until the first approver or rejector touches the milestone.
+    #[account(
+        seeds = [pda::MILESTONE_APPROVAL_SEED, escrow.key().as_ref(), &[milestone_idx]],
+        bump = milestone_approval.bump,
+    )]
+    pub milestone_approval: Option<Account<'info, MilestoneApproval>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateFundingSchedule<'info> {
+    #[account(
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status == Status::Initialized || escrow.status == Status::Funded @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        init,
+        payer = funder,
+        space = 8 + 32 + 32 + (4 + (8 + 8) * 10) + 1 + 1,  // 238 bytes
+        seeds = [pda::SCHEDULED_FUNDING_SEED, escrow.key().as_ref()],
+        bump,
+    )]
+    pub scheduled_funding: Account<'info, ScheduledFunding>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ClaimScheduledFunding<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status == Status::Initialized
+            || escrow.status == Status::Funded
+            || escrow.status == Status::Active @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [pda::SCHEDULED_FUNDING_SEED, escrow.key().as_ref()],
+        bump = scheduled_funding.bump,
+    )]
+    pub scheduled_funding: Account<'info, ScheduledFunding>,
+}
+
+#[derive(Accounts)]
+pub struct CloseFundingSchedule<'info> {
+    #[account(
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status == Status::Cancelled || escrow.status == Status::Completed @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        close = funder,
+        seeds = [pda::SCHEDULED_FUNDING_SEED, escrow.key().as_ref()],
+        bump = scheduled_funding.bump,
+    )]
+    pub scheduled_funding: Account<'info, ScheduledFunding>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExportEscrow<'info> {
+    #[account(
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[derive(Accounts)]
+pub struct RequiredTopup<'info> {
+    #[account(
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyRefundAll<'info> {
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, funder.key().as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.status != Status::Cancelled @ ErrorCode::InvalidStatus,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        seeds = [pda::MILESTONE_CONFIG_SEED, escrow.key().as_ref()],
+        bump = milestone_config.bump,
+    )]
+    pub milestone_config: Account<'info, MilestoneConfig>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProgressBps<'info> {
+    #[account(
+        seeds = [pda::ESCROW_SEED, escrow.funder.as_ref(), escrow.recipient.as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[derive(Accounts)]
+pub struct EscrowsStatusBatch {}
+
 // ── Account Data Structs ────────────────────────────────────────
 
+/// The escrow PDA is a program-owned data account that also holds the escrowed SOL.
+/// Lamports arrive through System Program transfers from the funder and leave through
+/// direct lamport debits through `move_lamports`, never below the rent-exempt minimum
+/// for its data.
 #[account]
 pub struct Escrow {
     pub funder: Pubkey,
@@ -558,6 +1652,21 @@ pub struct Escrow {
     pub deadline: i64,
     pub bump: u8,
     pub has_multi_approval: bool,
+    pub on_complete_action: OnCompleteAction,
+    /// Non-refundable support; spendable by releases but excluded from refunds.
+    pub donations: u64,
+    pub release_holdback_seconds: i64,
+    pub status_message: String,
+    /// Seconds after first funding before milestone 0 can be released.
+    pub first_release_cliff: i64,
+    pub first_funded_at: i64,
+    pub created_at: i64,
+    /// Seconds a dispute may stay open before its default outcome can be applied; 0 disables.
+    pub dispute_timeout: i64,
+    pub dispute_default: DisputeOutcome,
+    /// Bit `i` set once milestone `i` has been fully paid out.
+    pub released_bitset: u16,
+    pub donations_claimed: u64,
 }
 
 #[account]
@@ -575,12 +1684,78 @@ pub struct MilestoneApproval {
     pub approvals: Vec<ApprovalRecord>,
     pub status: MilestoneStatus,
     pub bump: u8,
+    /// Set when the milestone is disputed.
+    pub disputer: Pubkey,
+    pub disputed_at: i64,
+}
+
+#[account]
+pub struct MilestoneDocument {
+    pub escrow: Pubkey,
+    pub milestone_idx: u8,
+    pub doc_index: u8,
+    pub doc_hash: [u8; 32],
+    pub doc_uri: String,
+    pub attached_by: Pubkey,
+    pub attached_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct PayeeHoldback {
+    pub escrow: Pubkey,
+    pub milestone_idx: u8,
+    pub amount: u64,
+    pub claimable_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct ScheduledFunding {
+    pub escrow: Pubkey,
+    pub funder: Pubkey,
+    pub tranches: Vec<FundingTranche>,
+    pub tranches_claimed: u8,
+    pub bump: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
 pub struct Milestone {
     pub amount: u64,
     pub description: Option<String>,
+    /// Optional payout schedule; when non-empty the amounts must sum to `amount`.
+    pub installments: Vec<Installment>,
+    pub installments_paid: u8,
+    /// When non-zero the milestone pays this share of `total_funded` instead of `amount`.
+    pub payout_bps: u16,
+    /// When non-zero anyone can approve the milestone once `funding_bps` reaches this.
+    pub funding_unlock_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct Installment {
+    pub unlock_time: i64,
+    pub amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FundingTranche {
+    pub unlock_time: i64,
+    pub amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Progress {
+    pub completion_bps: u16,
+    pub funding_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EscrowStatusEntry {
+    pub escrow: Pubkey,
+    pub total_funded: u64,
+    pub completion_bps: u16,
+    pub status: Status,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -607,6 +1782,37 @@ pub enum MilestoneStatus {
     Resolved,
 }
 
+/// Outcome applied when a dispute times out without resolution.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum DisputeOutcome {
+    /// Milestone counts as approved and can be released to the recipient.
+    FavorRecipient,
+    /// Escrow is cancelled and unreleased funds return to the funder.
+    FavorFunder,
+}
+
+/// What to do with residual escrow funds after the final milestone is released.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum OnCompleteAction {
+    Hold,
+    RefundFunder,
+}
+
+/// Reason codes returned by `is_releasable`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ReleaseBlocker {
+    Releasable = 0,
+    InvalidIndex = 1,
+    NotApproved = 2,
+    Disputed = 3,
+    NothingToRelease = 4,
+    InsufficientFunds = 5,
+    CliffNotReached = 6,
+    AlreadyReleased = 7,
+    OverRelease = 8,
+}
+
 impl Default for MilestoneStatus {
     fn default() -> Self {
         MilestoneStatus::Pending
@@ -614,14 +1820,219 @@ impl Default for MilestoneStatus {
 }
 
 impl Escrow {
-    pub fn escrow_seeds(&self) -> [&[u8]; 4] {
-        [
-            b"escrow",
-            self.funder.as_ref(),
-            self.recipient.as_ref(),
-            &[self.bump],
-        ]
+    pub fn is_released(&self, milestone_idx: u8) -> bool {
+        milestone_idx < 16 && self.released_bitset & (1 << milestone_idx) != 0
+    }
+
+    pub fn mark_released(&mut self, milestone_idx: u8) {
+        self.released_bitset |= 1 << milestone_idx;
+    }
+
+    /// Zero-amount milestones have nothing to pay and count as released.
+    pub fn all_milestones_released(&self) -> bool {
+        self.milestones
+            .iter()
+            .enumerate()
+            .all(|(i, m)| (m.amount == 0 && m.payout_bps == 0) || self.is_released(i as u8))
+    }
+
+    /// Approved milestones as basis points (0-10000) of all milestones.
+    pub fn completion_bps(&self) -> u16 {
+        if self.milestones.is_empty() {
+            return 0;
+        }
+        (self.current_milestone as u64 * 10_000 / self.milestones.len() as u64) as u16
+    }
+
+    /// Funding received as basis points of the funding every milestone needs, capped at
+    /// 10000. Percentage-only escrows are fully funded by any deposit.
+    pub fn funding_bps(&self) -> Result<u16> {
+        let required = self.required_funding()?;
+        if required == 0 {
+            let funded = self.total_funded > 0 && self.milestones.iter().any(|m| m.payout_bps > 0);
+            return Ok(if funded { 10_000 } else { 0 });
+        }
+        let bps = (self.total_funded as u128 * 10_000 / required as u128).min(10_000);
+        Ok(bps as u16)
     }
+
+    /// Funding at which every milestone is backed: the fixed amounts plus the
+    /// percentage milestones' share of that same funding.
+    pub fn required_funding(&self) -> Result<u64> {
+        let fixed = self
+            .milestones
+            .iter()
+            .try_fold(0u64, |acc, m| acc.checked_add(m.amount))
+            .ok_or(ErrorCode::Overflow)?;
+        let bps: u64 = self.milestones.iter().map(|m| m.payout_bps as u64).sum();
+        if fixed == 0 || bps >= 10_000 {
+            return Ok(0);
+        }
+        let unshared = 10_000 - bps as u128;
+        let required = (fixed as u128 * 10_000 + unshared - 1) / unshared;
+        u64::try_from(required).map_err(|_| error!(ErrorCode::Overflow))
+    }
+
+    /// Records the first time funds land in the escrow.
+    pub fn mark_funded(&mut self, now: i64) {
+        if self.first_funded_at == 0 {
+            self.first_funded_at = now;
+        }
+    }
+
+    /// A holdback is only reachable through `release_milestone_funds` (multi-approval),
+    /// and its per-milestone PDA cannot track installments paid at different times.
+    pub fn validate_holdback(&self) -> Result<()> {
+        if self.release_holdback_seconds == 0 {
+            return Ok(());
+        }
+        require!(self.has_multi_approval, ErrorCode::HoldbackRequiresMultiApproval);
+        require!(
+            self.milestones.iter().all(|m| m.installments.is_empty()),
+            ErrorCode::HoldbackWithInstallments
+        );
+        Ok(())
+    }
+
+    pub fn first_release_cliff_passed(&self, now: i64) -> bool {
+        self.first_release_cliff == 0
+            || (self.first_funded_at != 0
+                && now >= self.first_funded_at.saturating_add(self.first_release_cliff))
+    }
+
+    /// New `total_released` after paying `amount`, enforcing that releases never exceed
+    /// funding plus donations even if the account holds extra lamports.
+    pub fn checked_release_total(&self, amount: u64) -> Result<u64> {
+        let new_released = self.total_released.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        let spendable = self.total_funded.checked_add(self.donations).ok_or(ErrorCode::Overflow)?;
+        require!(new_released <= spendable, ErrorCode::OverReleaseDetected);
+        Ok(new_released)
+    }
+
+    /// Donations not yet paid out. Releases draw on funding first, so only releases
+    /// beyond `total_funded` consume donations.
+    pub fn unspent_donations(&self) -> u64 {
+        let spent = self.total_released.saturating_sub(self.total_funded);
+        self.donations.saturating_sub(spent).saturating_sub(self.donations_claimed)
+    }
+
+    /// What unreleased milestones still pay at the current funding, net of installments
+    /// already paid.
+    pub fn outstanding_payouts(&self) -> Result<u64> {
+        let mut total = 0u64;
+        for (i, m) in self.milestones.iter().enumerate() {
+            if self.is_released(i as u8) {
+                continue;
+            }
+            let remaining = m.payout_amount(self.total_funded)?.saturating_sub(m.paid_amount()?);
+            total = total.checked_add(remaining).ok_or(ErrorCode::Overflow)?;
+        }
+        Ok(total)
+    }
+}
+
+impl Milestone {
+    pub const MAX_INSTALLMENTS: usize = 4;
+
+    pub fn validate_installments(&self) -> Result<()> {
+        require!(self.installments_paid == 0, ErrorCode::InvalidInstallments);
+        if self.installments.is_empty() {
+            return Ok(());
+        }
+        require!(self.installments.len() <= Self::MAX_INSTALLMENTS, ErrorCode::InvalidInstallments);
+        let mut total = 0u64;
+        let mut prev_unlock = i64::MIN;
+        for i in &self.installments {
+            require!(i.unlock_time >= prev_unlock, ErrorCode::InvalidInstallments);
+            prev_unlock = i.unlock_time;
+            total = total.checked_add(i.amount).ok_or(ErrorCode::Overflow)?;
+        }
+        require!(total == self.amount, ErrorCode::InvalidInstallments);
+        Ok(())
+    }
+
+    /// Percentage milestones must leave `amount` at zero and cannot use installments.
+    pub fn validate_payout_bps(&self) -> Result<()> {
+        if self.payout_bps == 0 {
+            return Ok(());
+        }
+        require!(self.payout_bps <= 10_000, ErrorCode::InvalidPayoutBps);
+        require!(self.amount == 0 && self.installments.is_empty(), ErrorCode::InvalidPayoutBps);
+        Ok(())
+    }
+
+    /// Full payout of the milestone given the escrow's `total_funded`.
+    pub fn payout_amount(&self, total_funded: u64) -> Result<u64> {
+        if self.payout_bps == 0 {
+            return Ok(self.amount);
+        }
+        // payout_bps <= 10000, so the result never exceeds total_funded
+        Ok((total_funded as u128 * self.payout_bps as u128 / 10_000) as u64)
+    }
+
+    /// Installment amounts already paid out.
+    pub fn paid_amount(&self) -> Result<u64> {
+        self.installments[..self.installments_paid as usize]
+            .iter()
+            .try_fold(0u64, |acc, i| acc.checked_add(i.amount))
+            .ok_or_else(|| error!(ErrorCode::Overflow))
+    }
+
+    /// Amount payable at `now` and the resulting `installments_paid` count.
+    pub fn due_amount(&self, now: i64, total_funded: u64) -> Result<(u64, u8)> {
+        if self.installments.is_empty() {
+            return Ok((self.payout_amount(total_funded)?, 0));
+        }
+        let mut amount = 0u64;
+        let mut next = self.installments_paid as usize;
+        while next < self.installments.len() && self.installments[next].unlock_time <= now {
+            amount = amount.checked_add(self.installments[next].amount).ok_or(ErrorCode::Overflow)?;
+            next += 1;
+        }
+        Ok((amount, next as u8))
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────
+
+/// Settles residual funds once the final obligation has been paid out. With
+/// `OnCompleteAction::RefundFunder` the unreleased funding goes back to the funder.
+fn settle_residual<'info>(escrow: &mut Account<'info, Escrow>, funder: &AccountInfo<'info>) -> Result<()> {
+    if escrow.status != Status::Completed
+        || !escrow.all_milestones_released()
+        || escrow.on_complete_action != OnCompleteAction::RefundFunder
+    {
+        return Ok(());
+    }
+    let escrow_info = escrow.to_account_info();
+    let residual = escrow
+        .total_funded
+        .saturating_sub(escrow.total_released)
+        .min(spendable_lamports(&escrow_info)?);
+    if residual > 0 {
+        move_lamports(&escrow_info, funder, residual)?;
+    }
+    emit!(EscrowResidualSettled {
+        escrow: escrow.key(),
+        action: OnCompleteAction::RefundFunder,
+        amount: residual,
+    });
+    Ok(())
+}
+
+/// Lamports `info` can pay out while staying rent-exempt for its data.
+fn spendable_lamports(info: &AccountInfo) -> Result<u64> {
+    let rent_floor = Rent::get()?.minimum_balance(info.data_len());
+    Ok(info.lamports().saturating_sub(rent_floor))
+}
+
+/// Moves lamports out of a program-owned account (the escrow or one of its PDAs)
+/// by direct debit, never below the rent-exempt minimum for its data.
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    require!(amount <= spendable_lamports(from)?, ErrorCode::InsufficientFunds);
+    **from.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
 }
 
 // ── Error Codes ─────────────────────────────────────────────────
@@ -678,4 +2089,64 @@ pub enum ErrorCode {
     NotDisputed,
     #[msg("Unauthorized resolve")]
     UnauthorizedResolve,
+    #[msg("Invalid funding schedule (1-10 tranches, ascending unlock times)")]
+    InvalidSchedule,
+    #[msg("No funding tranche is due yet")]
+    NoTrancheDue,
+    #[msg("Invalid installments (max 4, ascending unlock times, must sum to milestone amount)")]
+    InvalidInstallments,
+    #[msg("Milestone approval account does not belong to this escrow")]
+    InvalidApprovalAccount,
+    #[msg("Account is not the escrow funder")]
+    InvalidFunder,
+    #[msg("Milestone amount must be nonzero unless zero amounts are allowed")]
+    InvalidMilestoneAmount,
+    #[msg("Every configured approver must co-sign")]
+    MissingApproverSignature,
+    #[msg("Document URI too long (max 200 chars)")]
+    UriTooLong,
+    #[msg("Release would exceed total funded")]
+    OverReleaseDetected,
+    #[msg("Holdback window must not be negative")]
+    InvalidHoldback,
+    #[msg("Payee holdback account required for this escrow")]
+    HoldbackAccountRequired,
+    #[msg("Holdback is still within its clawback window")]
+    HoldbackLocked,
+    #[msg("Holdback clawback window has closed")]
+    HoldbackWindowClosed,
+    #[msg("Status message too long (max 128 bytes)")]
+    StatusMessageTooLong,
+    #[msg("Only the funder or recipient can set the status message")]
+    UnauthorizedStatusMessage,
+    #[msg("Cliff must not be negative")]
+    InvalidCliff,
+    #[msg("First milestone is still within its release cliff")]
+    FirstReleaseCliff,
+    #[msg("Dispute timeout must not be negative")]
+    InvalidDisputeTimeout,
+    #[msg("Dispute timeout is not configured for this escrow")]
+    DisputeTimeoutDisabled,
+    #[msg("Dispute has not yet timed out")]
+    DisputeNotExpired,
+    #[msg("Milestones with installment schedules cannot be split")]
+    CannotSplitMilestone,
+    #[msg("Milestone already released")]
+    MilestoneAlreadyReleased,
+    #[msg("Too many escrows in one batch")]
+    BatchTooLarge,
+    #[msg("Escrow balance did not increase by the funded amount")]
+    FundingShortfall,
+    #[msg("Invalid milestone payout percentage")]
+    InvalidPayoutBps,
+    #[msg("Invalid funding unlock percentage")]
+    InvalidFundingUnlock,
+    #[msg("Funding has not reached the milestone's unlock threshold")]
+    FundingThresholdNotMet,
+    #[msg("Escrows with a holdback must release per milestone")]
+    HoldbackRequiresMilestoneRelease,
+    #[msg("A holdback requires multi-approval")]
+    HoldbackRequiresMultiApproval,
+    #[msg("A holdback cannot be combined with installment milestones")]
+    HoldbackWithInstallments,
 }
@@ -0,0 +1,96 @@
+//! Seed constants and PDA derivations shared by the account contexts and clients.
+
+use anchor_lang::prelude::Pubkey;
+
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const MILESTONE_CONFIG_SEED: &[u8] = b"milestone_config";
+pub const MILESTONE_APPROVAL_SEED: &[u8] = b"milestone_approval";
+pub const SCHEDULED_FUNDING_SEED: &[u8] = b"scheduled_funding";
+pub const MILESTONE_DOCUMENT_SEED: &[u8] = b"milestone_document";
+pub const PAYEE_HOLDBACK_SEED: &[u8] = b"payee_holdback";
+
+pub fn escrow_pda(funder: &Pubkey, recipient: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ESCROW_SEED, funder.as_ref(), recipient.as_ref()], &crate::ID)
+}
+
+pub fn milestone_config_pda(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MILESTONE_CONFIG_SEED, escrow.as_ref()], &crate::ID)
+}
+
+pub fn milestone_approval_pda(escrow: &Pubkey, milestone_idx: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MILESTONE_APPROVAL_SEED, escrow.as_ref(), &[milestone_idx]],
+        &crate::ID,
+    )
+}
+
+pub fn scheduled_funding_pda(escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SCHEDULED_FUNDING_SEED, escrow.as_ref()], &crate::ID)
+}
+
+pub fn milestone_document_pda(escrow: &Pubkey, milestone_idx: u8, doc_index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MILESTONE_DOCUMENT_SEED, escrow.as_ref(), &[milestone_idx], &[doc_index]],
+        &crate::ID,
+    )
+}
+
+pub fn payee_holdback_pda(escrow: &Pubkey, milestone_idx: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PAYEE_HOLDBACK_SEED, escrow.as_ref(), &[milestone_idx]], &crate::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Seeds spelled out as literals, as in the `#[account(seeds = ...)]` attributes, so a
+    // changed constant or helper shows up as a mismatch here.
+    fn derive(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &crate::ID)
+    }
+
+    #[test]
+    fn helpers_match_account_seeds() {
+        let funder = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let escrow = escrow_pda(&funder, &recipient).0;
+
+        assert_eq!(
+            escrow_pda(&funder, &recipient),
+            derive(&[b"escrow", funder.as_ref(), recipient.as_ref()])
+        );
+        assert_eq!(
+            milestone_config_pda(&escrow),
+            derive(&[b"milestone_config", escrow.as_ref()])
+        );
+        assert_eq!(
+            milestone_approval_pda(&escrow, 3),
+            derive(&[b"milestone_approval", escrow.as_ref(), &[3]])
+        );
+        assert_eq!(
+            scheduled_funding_pda(&escrow),
+            derive(&[b"scheduled_funding", escrow.as_ref()])
+        );
+        assert_eq!(
+            milestone_document_pda(&escrow, 1, 2),
+            derive(&[b"milestone_document", escrow.as_ref(), &[1], &[2]])
+        );
+        assert_eq!(
+            payee_holdback_pda(&escrow, 4),
+            derive(&[b"payee_holdback", escrow.as_ref(), &[4]])
+        );
+    }
+
+    #[test]
+    fn milestone_index_changes_the_address() {
+        let escrow = Pubkey::new_unique();
+        assert_ne!(
+            milestone_approval_pda(&escrow, 0).0,
+            milestone_approval_pda(&escrow, 1).0
+        );
+        assert_ne!(
+            payee_holdback_pda(&escrow, 0).0,
+            payee_holdback_pda(&escrow, 1).0
+        );
+    }
+}
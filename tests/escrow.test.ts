@@ -14,8 +14,8 @@ describe(&quot;escrow&quot;, () =&gt; {
   let recipient = Keypair.generate();
   let escrowPda: PublicKey;
   let milestones = [
-    { amount: new anchor.BN(1000000000n), description: &quot;Milestone 1: Foundation&quot; }, // 1 SOL
-    { amount: new anchor.BN(2000000000n), description: &quot;Milestone 2: Installation&quot; },
+    { amount: new anchor.BN(1000000000n), description: &quot;Milestone 1: Foundation&quot;, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 }, // 1 SOL
+    { amount: new anchor.BN(2000000000n), description: &quot;Milestone 2: Installation&quot;, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 },
   ];
   const deadline = Math.floor(Date.now() / 1000) + 86400; // 24h
 
@@ -34,7 +34,7 @@ describe(&quot;escrow&quot;, () =&gt; {
 
   it(&quot;Initializes escrow&quot;, async () =&gt; {
     const tx = await program.methods
-      .initializeEscrow(milestones, new anchor.BN(deadline))
+      .initializeEscrow(milestones, new anchor.BN(deadline), false)
       .accounts({
         escrow: escrowPda,
         funder: funder.publicKey,
@@ -69,7 +69,7 @@ describe(&quot;escrow&quot;, () =&gt; {
   // Fund after init
   it(&quot;Funder funds escrow&quot;, async () =&gt; {
     await program.methods
-      .initializeEscrow(milestones, new anchor.BN(deadline))
+      .initializeEscrow(milestones, new anchor.BN(deadline), false)
       .accounts({escrow: escrowPda, funder: funder.publicKey, recipient: recipient.publicKey, systemProgram: SystemProgram.programId})
       .rpc();
 
@@ -137,4 +137,964 @@ describe(&quot;escrow&quot;, () =&gt; {
     // Skip detailed or use mock clock if possible
   });
 
+  it("Reports why a milestone is not releasable", async () => {
+    const other = Keypair.generate();
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), other.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeEscrow(milestones, new anchor.BN(deadline), false)
+      .accounts({ escrow: pda, funder: funder.publicKey, recipient: other.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    // Out-of-range index
+    const invalidIdx = await program.methods
+      .isReleasable(9)
+      .accounts({ escrow: pda, milestoneApproval: null })
+      .view();
+    assert.equal(invalidIdx, 1);
+
+    // No approval account yet
+    const notApproved = await program.methods
+      .isReleasable(0)
+      .accounts({ escrow: pda, milestoneApproval: null })
+      .view();
+    assert.equal(notApproved, 2);
+
+    const reason = (e: any) => program.methods
+      .isReleasable(0)
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0) })
+      .view();
+
+    // Approved but already paid out
+    const released = await setupMultiEscrow(milestones, 3_000_000_000);
+    await approveAll(released, 0);
+    await program.methods
+      .releaseMilestoneFunds(0)
+      .accounts({ escrow: released.pda, milestoneApproval: approvalPda(released.pda, 0), recipient: released.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+      .signers([released.payee])
+      .rpc();
+    assert.equal(await reason(released), 7);
+
+    // Under dispute
+    const disputed = await setupMultiEscrow(milestones, 3_000_000_000);
+    await program.methods
+      .rejectMilestone(0, "inverter missing")
+      .accounts({ escrow: disputed.pda, milestoneConfig: disputed.configPda, milestoneApproval: approvalPda(disputed.pda, 0), approver: disputed.approvers[0].publicKey, systemProgram: SystemProgram.programId })
+      .signers([disputed.approvers[0]])
+      .rpc();
+    await program.methods
+      .disputeMilestone(0)
+      .accounts({ escrow: disputed.pda, milestoneApproval: approvalPda(disputed.pda, 0), disputer: disputed.payee.publicKey })
+      .signers([disputed.payee])
+      .rpc();
+    assert.equal(await reason(disputed), 3);
+
+    // Approved, but the escrow holds less than the milestone pays
+    const underfunded = await setupMultiEscrow(milestones, 500_000_000);
+    await approveAll(underfunded, 0);
+    assert.equal(await reason(underfunded), 5);
+
+    // Lamports sent straight to the PDA cover the payout, but recorded funding does not
+    const topped = await setupMultiEscrow(milestones, 500_000_000);
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      SystemProgram.transfer({ fromPubkey: funder.publicKey, toPubkey: topped.pda, lamports: 1_000_000_000 })
+    ));
+    await approveAll(topped, 0);
+    assert.equal(await reason(topped), 8);
+  });
+
+  // Validator clock, which can drift from Date.now() on a loaded test validator.
+  async function clockNow() {
+    const slot = await provider.connection.getSlot("confirmed");
+    return await provider.connection.getBlockTime(slot);
+  }
+
+  // Polls until the validator clock reaches `unixTime`.
+  async function waitForClock(unixTime: number) {
+    for (let i = 0; i < 120; i++) {
+      if ((await clockNow()) >= unixTime) return;
+      await new Promise(r => setTimeout(r, 500));
+    }
+    throw new Error(`validator clock never reached ${unixTime}`);
+  }
+
+  it("Claims scheduled funding tranches as they unlock", async () => {
+    const other = Keypair.generate();
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), other.publicKey.toBuffer()],
+      program.programId
+    );
+    const [schedulePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("scheduled_funding"), pda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeEscrow(milestones, new anchor.BN(deadline), false)
+      .accounts({ escrow: pda, funder: funder.publicKey, recipient: other.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const now = await clockNow();
+    await program.methods
+      .createFundingSchedule([
+        { unlockTime: new anchor.BN(now - 60), amount: new anchor.BN(500_000_000) },
+        { unlockTime: new anchor.BN(now + 3), amount: new anchor.BN(300_000_000) },
+        { unlockTime: new anchor.BN(now + 86400), amount: new anchor.BN(700_000_000) },
+      ])
+      .accounts({ escrow: pda, scheduledFunding: schedulePda, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const claim = () => program.methods
+      .claimScheduledFunding()
+      .accounts({ escrow: pda, scheduledFunding: schedulePda })
+      .rpc();
+
+    await claim();
+    let escrow = await program.account.escrow.fetch(pda);
+    assert.equal(escrow.totalFunded.toNumber(), 500_000_000);
+    assert.deepEqual(escrow.status, { funded: {} });
+
+    // Second tranche is still locked
+    await expect(claim()).to.be.rejectedWith(/NoTrancheDue/);
+
+    // Once it unlocks, its lamports move from the schedule into the escrow
+    await waitForClock(now + 3);
+    const escrowBefore = await provider.connection.getBalance(pda);
+    const scheduleBefore = await provider.connection.getBalance(schedulePda);
+    await claim();
+    escrow = await program.account.escrow.fetch(pda);
+    assert.equal(escrow.totalFunded.toNumber(), 800_000_000);
+    assert.equal(await provider.connection.getBalance(pda) - escrowBefore, 300_000_000);
+    assert.equal(scheduleBefore - await provider.connection.getBalance(schedulePda), 300_000_000);
+
+    // After cancelling, the funder reclaims the never-claimed tranche by closing the schedule
+    await program.methods.cancelEscrow()
+      .accounts({ escrow: pda, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const funderBefore = await provider.connection.getBalance(funder.publicKey);
+    await program.methods.closeFundingSchedule()
+      .accounts({ escrow: pda, scheduledFunding: schedulePda, funder: funder.publicKey })
+      .rpc();
+    const funderAfter = await provider.connection.getBalance(funder.publicKey);
+    assert.isAtLeast(funderAfter - funderBefore, 700_000_000 - 10_000);
+    assert.isNull(await provider.connection.getAccountInfo(schedulePda));
+  });
+
+  // Creates a fresh 2-of-2 multi-approval escrow and funds it.
+  async function setupMultiEscrow(ms: any[], fundAmount: number, beforeFund?: (pda: PublicKey) => Promise<void>) {
+    const payee = Keypair.generate();
+    const approvers = [Keypair.generate(), Keypair.generate()];
+    for (const kp of [payee, ...approvers]) {
+      await provider.connection.requestAirdrop(kp.publicKey, LAMPORTS_PER_SOL);
+    }
+    await new Promise(r => setTimeout(r, 1000));
+
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), payee.publicKey.toBuffer()],
+      program.programId
+    );
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("milestone_config"), pda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeEscrow(ms, new anchor.BN(deadline), false)
+      .accounts({ escrow: pda, funder: funder.publicKey, recipient: payee.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .configureMilestones(approvers.map(a => a.publicKey), 2)
+      .accounts({ escrow: pda, milestoneConfig: configPda, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    if (beforeFund) await beforeFund(pda);
+    await program.methods
+      .fundEscrow(new anchor.BN(fundAmount))
+      .accounts({ escrow: pda, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    return { pda, configPda, payee, approvers };
+  }
+
+  function approvalPda(escrow: PublicKey, idx: number) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("milestone_approval"), escrow.toBuffer(), Buffer.from([idx])],
+      program.programId
+    )[0];
+  }
+
+  async function approveAll(e: { pda: PublicKey, configPda: PublicKey, approvers: Keypair[] }, idx: number) {
+    for (const a of e.approvers) {
+      await program.methods
+        .approveMilestoneMulti(idx)
+        .accounts({
+          escrow: e.pda,
+          milestoneConfig: e.configPda,
+          milestoneApproval: approvalPda(e.pda, idx),
+          approver: a.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([a])
+        .rpc();
+    }
+  }
+
+  it("Pays due installments and holds back locked ones", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const ms = [{
+      amount: new anchor.BN(900_000_000),
+      description: null,
+      installments: [
+        { unlockTime: new anchor.BN(now - 120), amount: new anchor.BN(300_000_000) },
+        { unlockTime: new anchor.BN(now - 60), amount: new anchor.BN(300_000_000) },
+        { unlockTime: new anchor.BN(now + 86400), amount: new anchor.BN(300_000_000) },
+      ],
+      installmentsPaid: 0,
+      payoutBps: 0,
+      fundingUnlockBps: 0,
+    }];
+    const e = await setupMultiEscrow(ms, 900_000_000);
+    await approveAll(e, 0);
+
+    await program.methods
+      .releaseMilestoneFunds(0)
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+      .signers([e.payee])
+      .rpc();
+
+    const escrow = await program.account.escrow.fetch(e.pda);
+    assert.equal(escrow.totalReleased.toNumber(), 600_000_000);
+    assert.equal(escrow.milestones[0].installmentsPaid, 2);
+
+    await expect(
+      program.methods
+        .releaseMilestoneFunds(0)
+        .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+        .signers([e.payee])
+        .rpc()
+    ).to.be.rejectedWith(/NothingToRelease/);
+  });
+
+  it("Exports a compact escrow record that decodes client-side", async () => {
+    const ms = [
+      milestones[0],
+      { amount: new anchor.BN(0), description: null, installments: [], installmentsPaid: 0, payoutBps: 2500, fundingUnlockBps: 0 },
+    ];
+    const e = await setupMultiEscrow(ms, 3_000_000_000);
+    await approveAll(e, 0);
+
+    const raw: Buffer = Buffer.from(await program.methods
+      .exportEscrow()
+      .accounts({ escrow: e.pda })
+      .remainingAccounts([{ pubkey: approvalPda(e.pda, 0), isSigner: false, isWritable: false }])
+      .view());
+
+    let o = 0;
+    const version = raw.readUInt8(o); o += 1;
+    const escrowKey = new PublicKey(raw.subarray(o, o + 32)); o += 32;
+    o += 64; // funder, recipient
+    const status = raw.readUInt8(o); o += 1;
+    const current = raw.readUInt8(o); o += 1;
+    const funded = raw.readBigUInt64LE(o); o += 24; // funded, released, deadline
+    const count = raw.readUInt8(o); o += 1;
+    const records = [];
+    for (let i = 0; i < count; i++) {
+      records.push({ amount: raw.readBigUInt64LE(o), payoutBps: raw.readUInt16LE(o + 8), status: raw.readUInt8(o + 10) });
+      o += 11;
+    }
+
+    assert.equal(version, 1);
+    assert.equal(escrowKey.toBase58(), e.pda.toBase58());
+    assert.equal(status, 2); // Active
+    assert.equal(current, 1);
+    assert.equal(funded, 3_000_000_000n);
+    assert.deepEqual(records, [
+      { amount: 1_000_000_000n, payoutBps: 0, status: 1 }, // Approved
+      { amount: 0n, payoutBps: 2500, status: 0 }, // Pending, 25% of funding
+    ]);
+  });
+
+  it("Refunds residual funds to the funder after the final release", async () => {
+    const single = [{ amount: new anchor.BN(1_000_000_000), description: null, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 }];
+    const refundOnComplete = (pda: PublicKey) =>
+      program.methods.setOnCompleteAction({ refundFunder: {} }).accounts({ escrow: pda, funder: funder.publicKey }).rpc().then(() => {});
+
+    const e = await setupMultiEscrow(single, 1_500_000_000, refundOnComplete);
+    await approveAll(e, 0);
+    let before = await provider.connection.getBalance(funder.publicKey);
+    await program.methods
+      .releaseMilestoneFunds(0)
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+      .signers([e.payee])
+      .rpc();
+    let after = await provider.connection.getBalance(funder.publicKey);
+    // Funder also pays the transaction fee
+    assert.approximately(after - before, 500_000_000, 10_000);
+
+    // Same settlement when the final release goes through release_funds
+    const viaReleaseFunds = await setupMultiEscrow(single, 1_500_000_000, refundOnComplete);
+    await approveAll(viaReleaseFunds, 0);
+    before = await provider.connection.getBalance(funder.publicKey);
+    await program.methods.releaseFunds()
+      .accounts({ escrow: viaReleaseFunds.pda, recipient: viaReleaseFunds.payee.publicKey, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .signers([viaReleaseFunds.payee])
+      .rpc();
+    after = await provider.connection.getBalance(funder.publicKey);
+    assert.approximately(after - before, 500_000_000, 10_000);
+
+    // Hold (the default) leaves the residual in the escrow after the final release
+    const held = await setupMultiEscrow(single, 1_500_000_000);
+    await approveAll(held, 0);
+    const escrowBefore = await provider.connection.getBalance(held.pda);
+    await program.methods
+      .releaseMilestoneFunds(0)
+      .accounts({ escrow: held.pda, milestoneApproval: approvalPda(held.pda, 0), recipient: held.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+      .signers([held.payee])
+      .rpc();
+    const escrowAfter = await provider.connection.getBalance(held.pda);
+    assert.equal(escrowBefore - escrowAfter, 1_000_000_000);
+    const heldEscrow = await program.account.escrow.fetch(held.pda);
+    assert.deepEqual(heldEscrow.status, { completed: {} });
+    assert.equal(heldEscrow.totalFunded.toNumber() - heldEscrow.totalReleased.toNumber(), 500_000_000);
+  });
+
+  it("Rejects zero-amount milestones unless explicitly allowed", async () => {
+    const ack = [{ amount: new anchor.BN(0), description: "Grid inspection", installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 }];
+
+    const rejected = Keypair.generate();
+    const [rejectedPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), rejected.publicKey.toBuffer()],
+      program.programId
+    );
+    await expect(
+      program.methods
+        .initializeEscrow(ack, new anchor.BN(deadline), false)
+        .accounts({ escrow: rejectedPda, funder: funder.publicKey, recipient: rejected.publicKey, systemProgram: SystemProgram.programId })
+        .rpc()
+    ).to.be.rejectedWith(/InvalidMilestoneAmount/);
+
+    const allowed = Keypair.generate();
+    const [allowedPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), allowed.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeEscrow(ack, new anchor.BN(deadline), true)
+      .accounts({ escrow: allowedPda, funder: funder.publicKey, recipient: allowed.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const escrow = await program.account.escrow.fetch(allowedPda);
+    assert.equal(escrow.milestones[0].amount.toNumber(), 0);
+  });
+
+  it("Computes the top-up needed to back every milestone", async () => {
+    const under = await setupMultiEscrow(milestones, 1_000_000_000);
+    const shortfall = await program.methods
+      .requiredTopup()
+      .accounts({ escrow: under.pda })
+      .view();
+    assert.equal(shortfall.toNumber(), 2_000_000_000);
+
+    const full = await setupMultiEscrow(milestones, 3_000_000_000);
+    const none = await program.methods
+      .requiredTopup()
+      .accounts({ escrow: full.pda })
+      .view();
+    assert.equal(none.toNumber(), 0);
+
+    // A 50% milestone owes half of the 1 SOL funded on top of the fixed 1 SOL
+    const mixed = await setupMultiEscrow([
+      { amount: new anchor.BN(1_000_000_000), description: null, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 },
+      { amount: new anchor.BN(0), description: null, installments: [], installmentsPaid: 0, payoutBps: 5000, fundingUnlockBps: 0 },
+    ], 1_000_000_000);
+    const mixedShortfall = await program.methods
+      .requiredTopup()
+      .accounts({ escrow: mixed.pda })
+      .view();
+    assert.equal(mixedShortfall.toNumber(), 500_000_000);
+  });
+
+  it("Emergency refund requires every approver to co-sign", async () => {
+    const e = await setupMultiEscrow(milestones, 3_000_000_000);
+
+    await expect(
+      program.methods
+        .emergencyRefundAll()
+        .accounts({ escrow: e.pda, milestoneConfig: e.configPda, funder: funder.publicKey })
+        .remainingAccounts([{ pubkey: e.approvers[0].publicKey, isSigner: true, isWritable: false }])
+        .signers([e.approvers[0]])
+        .rpc()
+    ).to.be.rejectedWith(/MissingApproverSignature/);
+
+    await program.methods
+      .emergencyRefundAll()
+      .accounts({ escrow: e.pda, milestoneConfig: e.configPda, funder: funder.publicKey })
+      .remainingAccounts(e.approvers.map(a => ({ pubkey: a.publicKey, isSigner: true, isWritable: false })))
+      .signers(e.approvers)
+      .rpc();
+    const escrow = await program.account.escrow.fetch(e.pda);
+    assert.deepEqual(escrow.status, { cancelled: {} });
+  });
+
+  it("Attaches multiple documents to one milestone", async () => {
+    const e = await setupMultiEscrow(milestones, 3_000_000_000);
+    const docPda = (docIdx: number) => PublicKey.findProgramAddressSync(
+      [Buffer.from("milestone_document"), e.pda.toBuffer(), Buffer.from([0]), Buffer.from([docIdx])],
+      program.programId
+    )[0];
+
+    for (const [docIdx, uri] of [[0, "ipfs://inspection-report"], [1, "ipfs://site-photos"]] as [number, string][]) {
+      await program.methods
+        .attachDocument(0, docIdx, Array.from(Buffer.alloc(32, docIdx + 1)), uri)
+        .accounts({
+          escrow: e.pda,
+          milestoneConfig: e.configPda,
+          milestoneDocument: docPda(docIdx),
+          approver: e.approvers[0].publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([e.approvers[0]])
+        .rpc();
+    }
+
+    const second = await program.account.milestoneDocument.fetch(docPda(1));
+    assert.equal(second.docIndex, 1);
+    assert.equal(second.docUri, "ipfs://site-photos");
+    assert.deepEqual(second.docHash, Array.from(Buffer.alloc(32, 2)));
+  });
+
+  it("Refuses to release more than was funded", async () => {
+    const single = [{ amount: new anchor.BN(1_000_000_000), description: null, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 }];
+    const e = await setupMultiEscrow(single, 500_000_000);
+
+    // Lamports sent outside fund_escrow are not funding
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      SystemProgram.transfer({ fromPubkey: funder.publicKey, toPubkey: e.pda, lamports: 1_000_000_000 })
+    ));
+    await approveAll(e, 0);
+
+    await expect(
+      program.methods
+        .releaseMilestoneFunds(0)
+        .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+        .signers([e.payee])
+        .rpc()
+    ).to.be.rejectedWith(/OverReleaseDetected/);
+  });
+
+  it("Keeps donations out of refunds", async () => {
+    const e = await setupMultiEscrow(milestones, 3_000_000_000);
+    const donor = Keypair.generate();
+    await provider.connection.requestAirdrop(donor.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise(r => setTimeout(r, 1000));
+
+    await program.methods
+      .donate(new anchor.BN(1_000_000_000))
+      .accounts({ escrow: e.pda, donor: donor.publicKey, systemProgram: SystemProgram.programId })
+      .signers([donor])
+      .rpc();
+
+    const before = await provider.connection.getBalance(e.pda);
+    await program.methods
+      .emergencyRefundAll()
+      .accounts({ escrow: e.pda, milestoneConfig: e.configPda, funder: funder.publicKey })
+      .remainingAccounts(e.approvers.map(a => ({ pubkey: a.publicKey, isSigner: true, isWritable: false })))
+      .signers(e.approvers)
+      .rpc();
+    const after = await provider.connection.getBalance(e.pda);
+
+    const escrow = await program.account.escrow.fetch(e.pda);
+    assert.equal(escrow.donations.toNumber(), 1_000_000_000);
+    assert.equal(before - after, 3_000_000_000);
+
+    // Unspent donations go to the recipient rather than staying locked in the escrow
+    const claimDonations = () => program.methods
+      .claimDonations()
+      .accounts({ escrow: e.pda, recipient: e.payee.publicKey })
+      .signers([e.payee])
+      .rpc();
+    await claimDonations();
+    assert.equal(after - await provider.connection.getBalance(e.pda), 1_000_000_000);
+    assert.equal((await program.account.escrow.fetch(e.pda)).donationsClaimed.toNumber(), 1_000_000_000);
+    await expect(claimDonations()).to.be.rejectedWith(/NothingToRelease/);
+  });
+
+  it("Holds back releases until the clawback window passes", async () => {
+    const holdbackPda = (escrow: PublicKey) => PublicKey.findProgramAddressSync(
+      [Buffer.from("payee_holdback"), escrow.toBuffer(), Buffer.from([0])],
+      program.programId
+    )[0];
+    const withHoldback = (seconds: number) => (pda: PublicKey) =>
+      program.methods.setReleaseHoldback(new anchor.BN(seconds)).accounts({ escrow: pda, funder: funder.publicKey }).rpc().then(() => {});
+    const release = (e: any) => program.methods
+      .releaseMilestoneFunds(0)
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: holdbackPda(e.pda), systemProgram: SystemProgram.programId })
+      .signers([e.payee])
+      .rpc();
+
+    // Long window: claim is locked, funder can reverse
+    const held = await setupMultiEscrow(milestones, 3_000_000_000, withHoldback(3600));
+    await approveAll(held, 0);
+    await release(held);
+    await expect(
+      program.methods.claimHoldback(0)
+        .accounts({ escrow: held.pda, payeeHoldback: holdbackPda(held.pda), recipient: held.payee.publicKey })
+        .signers([held.payee])
+        .rpc()
+    ).to.be.rejectedWith(/HoldbackLocked/);
+    const funderBefore = await provider.connection.getBalance(funder.publicKey);
+    await program.methods.reverseHoldback(0)
+      .accounts({ escrow: held.pda, payeeHoldback: holdbackPda(held.pda), funder: funder.publicKey, recipient: held.payee.publicKey })
+      .rpc();
+    const funderAfter = await provider.connection.getBalance(funder.publicKey);
+    assert.closeTo(funderAfter - funderBefore, 1_000_000_000, 10_000);
+    const reversed = await program.account.escrow.fetch(held.pda);
+    assert.equal(reversed.totalReleased.toNumber(), 0);
+    assert.equal(reversed.totalFunded.toNumber(), 2_000_000_000);
+
+    // Reversed milestone cannot be paid again through either release path
+    await expect(
+      program.methods.releaseFunds()
+        .accounts({ escrow: held.pda, recipient: held.payee.publicKey, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+        .signers([held.payee])
+        .rpc()
+    ).to.be.rejectedWith(/HoldbackRequiresMilestoneRelease/);
+    await expect(release(held)).to.be.rejectedWith(/MilestoneAlreadyReleased/);
+
+    // Short window: recipient claims after it elapses
+    const short = await setupMultiEscrow(milestones, 3_000_000_000, withHoldback(1));
+    await approveAll(short, 0);
+    await release(short);
+    await waitForClock((await program.account.payeeHoldback.fetch(holdbackPda(short.pda))).claimableAt.toNumber());
+    const before = await provider.connection.getBalance(short.payee.publicKey);
+    await program.methods.claimHoldback(0)
+      .accounts({ escrow: short.pda, payeeHoldback: holdbackPda(short.pda), recipient: short.payee.publicKey })
+      .signers([short.payee])
+      .rpc();
+    const after = await provider.connection.getBalance(short.payee.publicKey);
+    assert.isAtLeast(after - before, 1_000_000_000);
+  });
+
+  it("Only allows a holdback on multi-approval escrows without installments", async () => {
+    const other = Keypair.generate();
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), other.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeEscrow(milestones, new anchor.BN(deadline), false)
+      .accounts({ escrow: pda, funder: funder.publicKey, recipient: other.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    await expect(
+      program.methods.setReleaseHoldback(new anchor.BN(3600)).accounts({ escrow: pda, funder: funder.publicKey }).rpc()
+    ).to.be.rejectedWith(/HoldbackRequiresMultiApproval/);
+
+    const now = await clockNow();
+    const split = [{
+      amount: new anchor.BN(600_000_000),
+      description: null,
+      installments: [
+        { unlockTime: new anchor.BN(now - 60), amount: new anchor.BN(300_000_000) },
+        { unlockTime: new anchor.BN(now + 86400), amount: new anchor.BN(300_000_000) },
+      ],
+      installmentsPaid: 0,
+      payoutBps: 0,
+      fundingUnlockBps: 0,
+    }];
+    await expect(
+      setupMultiEscrow(split, 600_000_000, (p) =>
+        program.methods.setReleaseHoldback(new anchor.BN(3600)).accounts({ escrow: p, funder: funder.publicKey }).rpc().then(() => {}))
+    ).to.be.rejectedWith(/HoldbackWithInstallments/);
+  });
+
+  it("Sets and overwrites the status message", async () => {
+    const e = await setupMultiEscrow(milestones, 3_000_000_000);
+
+    await program.methods
+      .setStatusMessage("awaiting inspection")
+      .accounts({ escrow: e.pda, author: funder.publicKey })
+      .rpc();
+    assert.equal((await program.account.escrow.fetch(e.pda)).statusMessage, "awaiting inspection");
+
+    await program.methods
+      .setStatusMessage("grid connection delayed")
+      .accounts({ escrow: e.pda, author: e.payee.publicKey })
+      .signers([e.payee])
+      .rpc();
+    assert.equal((await program.account.escrow.fetch(e.pda)).statusMessage, "grid connection delayed");
+  });
+
+  it("Blocks the first release until the cliff passes", async () => {
+    const withCliff = (seconds: number) => (pda: PublicKey) =>
+      program.methods.setFirstReleaseCliff(new anchor.BN(seconds)).accounts({ escrow: pda, funder: funder.publicKey }).rpc().then(() => {});
+    const release = (e: any) => program.methods
+      .releaseMilestoneFunds(0)
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+      .signers([e.payee])
+      .rpc();
+
+    const locked = await setupMultiEscrow(milestones, 3_000_000_000, withCliff(3600));
+    await approveAll(locked, 0);
+    await expect(release(locked)).to.be.rejectedWith(/FirstReleaseCliff/);
+    await expect(
+      program.methods.releaseFunds()
+        .accounts({ escrow: locked.pda, recipient: locked.payee.publicKey, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+        .signers([locked.payee])
+        .rpc()
+    ).to.be.rejectedWith(/FirstReleaseCliff/);
+
+    const open = await setupMultiEscrow(milestones, 3_000_000_000, withCliff(1));
+    await approveAll(open, 0);
+    const opened = await program.account.escrow.fetch(open.pda);
+    await waitForClock(opened.firstFundedAt.toNumber() + opened.firstReleaseCliff.toNumber());
+    await release(open);
+    assert.equal((await program.account.escrow.fetch(open.pda)).totalReleased.toNumber(), 1_000_000_000);
+  });
+
+  // Names of the program events emitted by a confirmed transaction.
+  async function eventNames(sig: string) {
+    await provider.connection.confirmTransaction(sig, "confirmed");
+    const tx = await provider.connection.getTransaction(sig, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    return Array.from(parser.parseLogs(tx.meta.logMessages)).map(ev => ev.name);
+  }
+
+  it("Emits the dispute lifecycle events", async () => {
+    const { pda, configPda, payee, approvers } = await setupMultiEscrow(milestones, 3_000_000_000);
+
+    const rejectSig = await program.methods
+      .rejectMilestone(0, "panels not installed")
+      .accounts({ escrow: pda, milestoneConfig: configPda, milestoneApproval: approvalPda(pda, 0), approver: approvers[0].publicKey, systemProgram: SystemProgram.programId })
+      .signers([approvers[0]])
+      .rpc();
+    const disputeSig = await program.methods
+      .disputeMilestone(0)
+      .accounts({ escrow: pda, milestoneApproval: approvalPda(pda, 0), disputer: payee.publicKey })
+      .signers([payee])
+      .rpc();
+    const resolveSig = await program.methods
+      .resolveDispute(0)
+      .accounts({ escrow: pda, milestoneApproval: approvalPda(pda, 0), funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    assert.deepEqual(await eventNames(rejectSig), ["MilestoneRejected"]);
+    assert.deepEqual(await eventNames(disputeSig), ["MilestoneDisputed"]);
+    assert.deepEqual(await eventNames(resolveSig), ["DisputeResolved"]);
+  });
+
+  it("Records the creation timestamp", async () => {
+    const e = await setupMultiEscrow(milestones, 1_000_000_000);
+    const escrow = await program.account.escrow.fetch(e.pda);
+    assert.isAbove(escrow.createdAt.toNumber(), 0);
+  });
+
+  it("Applies the default outcome once a dispute times out", async () => {
+    const withTimeout = (seconds: number) => (pda: PublicKey) =>
+      program.methods.setDisputeTimeout(new anchor.BN(seconds), { favorRecipient: {} })
+        .accounts({ escrow: pda, funder: funder.publicKey }).rpc().then(() => {});
+    const openDispute = async (e: any) => {
+      await program.methods
+        .rejectMilestone(0, "meter offline")
+        .accounts({ escrow: e.pda, milestoneConfig: e.configPda, milestoneApproval: approvalPda(e.pda, 0), approver: e.approvers[0].publicKey, systemProgram: SystemProgram.programId })
+        .signers([e.approvers[0]])
+        .rpc();
+      await program.methods
+        .disputeMilestone(0)
+        .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), disputer: e.payee.publicKey })
+        .signers([e.payee])
+        .rpc();
+    };
+    const timeout = (e: any) => program.methods
+      .resolveDisputeByTimeout(0)
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), funder: funder.publicKey, cranker: funder.publicKey })
+      .rpc();
+
+    const pending = await setupMultiEscrow(milestones, 3_000_000_000, withTimeout(3600));
+    await openDispute(pending);
+    await expect(timeout(pending)).to.be.rejectedWith(/DisputeNotExpired/);
+
+    const expired = await setupMultiEscrow(milestones, 3_000_000_000, withTimeout(1));
+    await openDispute(expired);
+    const disputedAt = (await program.account.milestoneApproval.fetch(approvalPda(expired.pda, 0))).disputedAt.toNumber();
+    await waitForClock(disputedAt + 1);
+    await timeout(expired);
+    const approval = await program.account.milestoneApproval.fetch(approvalPda(expired.pda, 0));
+    assert.deepEqual(approval.status, { approved: {} });
+    assert.equal((await program.account.escrow.fetch(expired.pda)).currentMilestone, 1);
+
+    // A dispute left open on a cancelled escrow cannot revive it
+    const cancelled = await setupMultiEscrow(milestones, 3_000_000_000, withTimeout(3600));
+    await openDispute(cancelled);
+    await program.methods.cancelEscrow()
+      .accounts({ escrow: cancelled.pda, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    await expect(timeout(cancelled)).to.be.rejectedWith(/InvalidStatus/);
+    assert.deepEqual((await program.account.escrow.fetch(cancelled.pda)).status, { cancelled: {} });
+  });
+
+  it("Reports progress in basis points", async () => {
+    const three = [1, 1, 1].map(() => ({ amount: new anchor.BN(1_000_000_000), description: null, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 }));
+    const e = await setupMultiEscrow(three, 1_500_000_000);
+    await approveAll(e, 0);
+
+    const progress = await program.methods
+      .progressBps()
+      .accounts({ escrow: e.pda })
+      .view();
+    assert.equal(progress.completionBps, 3333);
+    assert.equal(progress.fundingBps, 5000);
+
+    // Fixed 1 SOL plus 50% of funding needs 2 SOL; percentage-only escrows are backed by any deposit
+    const mixed = await setupMultiEscrow([
+      { amount: new anchor.BN(1_000_000_000), description: null, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 },
+      { amount: new anchor.BN(0), description: null, installments: [], installmentsPaid: 0, payoutBps: 5000, fundingUnlockBps: 0 },
+    ], 1_000_000_000);
+    assert.equal((await program.methods.progressBps().accounts({ escrow: mixed.pda }).view()).fundingBps, 5000);
+    const pct = await setupMultiEscrow([
+      { amount: new anchor.BN(0), description: null, installments: [], installmentsPaid: 0, payoutBps: 10000, fundingUnlockBps: 0 },
+    ], 1_000_000_000);
+    assert.equal((await program.methods.progressBps().accounts({ escrow: pct.pda }).view()).fundingBps, 10000);
+  });
+
+  it("Unlocks a funding-gated milestone at its threshold without approvers", async () => {
+    // Design payment unlocks at 25% of the 4 SOL total
+    const gated = [
+      { amount: new anchor.BN(1_000_000_000), description: null, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 2500 },
+      { amount: new anchor.BN(3_000_000_000), description: null, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 },
+    ];
+    const unlock = (e: any) => program.methods
+      .unlockFundedMilestone(0)
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), payer: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const below = await setupMultiEscrow(gated, 900_000_000);
+    await expect(unlock(below)).to.be.rejectedWith(/FundingThresholdNotMet/);
+
+    const at = await setupMultiEscrow(gated, 1_000_000_000);
+    await unlock(at);
+    assert.deepEqual((await program.account.milestoneApproval.fetch(approvalPda(at.pda, 0))).status, { approved: {} });
+    await program.methods
+      .releaseMilestoneFunds(0)
+      .accounts({ escrow: at.pda, milestoneApproval: approvalPda(at.pda, 0), recipient: at.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+      .signers([at.payee])
+      .rpc();
+    assert.equal((await program.account.escrow.fetch(at.pda)).totalReleased.toNumber(), 1_000_000_000);
+
+    // The next milestone has no funding gate, so it still needs its approvers
+    await expect(
+      program.methods
+        .unlockFundedMilestone(1)
+        .accounts({ escrow: at.pda, milestoneApproval: approvalPda(at.pda, 1), payer: funder.publicKey, systemProgram: SystemProgram.programId })
+        .rpc()
+    ).to.be.rejectedWith(/FundingThresholdNotMet/);
+  });
+
+  it("Splits a milestone while conserving the total", async () => {
+    const e = await setupMultiEscrow(milestones, 3_000_000_000);
+
+    await program.methods
+      .splitMilestone(1, new anchor.BN(500_000_000), "Milestone 3: Commissioning")
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 1), funder: funder.publicKey, recipient: e.payee.publicKey })
+      .signers([e.payee])
+      .rpc();
+
+    const escrow = await program.account.escrow.fetch(e.pda);
+    assert.equal(escrow.milestones.length, 3);
+    assert.equal(escrow.milestones[1].amount.toNumber(), 1_500_000_000);
+    assert.equal(escrow.milestones[2].amount.toNumber(), 500_000_000);
+    const total = escrow.milestones.reduce((sum, m) => sum + m.amount.toNumber(), 0);
+    assert.equal(total, 3_000_000_000);
+  });
+
+  it("Tracks released milestones in the bitset", async () => {
+    const six = [0, 1, 2, 3, 4, 5].map(() => ({ amount: new anchor.BN(100_000_000), description: null, installments: [], installmentsPaid: 0, payoutBps: 0, fundingUnlockBps: 0 }));
+    const e = await setupMultiEscrow(six, 600_000_000);
+    for (let i = 0; i <= 5; i++) {
+      await approveAll(e, i);
+    }
+    const release = () => program.methods
+      .releaseMilestoneFunds(5)
+      .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 5), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+      .signers([e.payee])
+      .rpc();
+
+    await release();
+    const escrow = await program.account.escrow.fetch(e.pda);
+    assert.equal(escrow.releasedBitset, 1 << 5);
+
+    await expect(release()).to.be.rejectedWith(/MilestoneAlreadyReleased/);
+  });
+
+  it("Returns statuses for a batch of escrows", async () => {
+    const a = await setupMultiEscrow(milestones, 1_000_000_000);
+    const b = await setupMultiEscrow(milestones, 3_000_000_000);
+    await approveAll(b, 0);
+    const c = await setupMultiEscrow(milestones, 2_000_000_000);
+
+    const entries = await program.methods
+      .escrowsStatusBatch()
+      .accounts({})
+      .remainingAccounts([a, b, c].map(e => ({ pubkey: e.pda, isSigner: false, isWritable: false })))
+      .view();
+
+    assert.deepEqual(entries.map(x => x.escrow.toBase58()), [a, b, c].map(e => e.pda.toBase58()));
+    assert.deepEqual(entries.map(x => x.totalFunded.toNumber()), [1_000_000_000, 3_000_000_000, 2_000_000_000]);
+    assert.deepEqual(entries.map(x => x.completionBps), [0, 5000, 0]);
+    assert.deepEqual(entries[1].status, { active: {} });
+  });
+
+  it("Funds, releases and refunds through the escrow data account", async () => {
+    const payee = Keypair.generate();
+    await provider.connection.requestAirdrop(payee.publicKey, LAMPORTS_PER_SOL);
+    await new Promise(r => setTimeout(r, 1000));
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), payee.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeEscrow(milestones, new anchor.BN(deadline), false)
+      .accounts({ escrow: pda, funder: funder.publicKey, recipient: payee.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const rentFloor = await provider.connection.getBalance(pda);
+    await program.methods
+      .fundEscrow(new anchor.BN(3_000_000_000))
+      .accounts({ escrow: pda, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    assert.equal(await provider.connection.getBalance(pda), rentFloor + 3_000_000_000);
+
+    await program.methods.approveMilestone(0)
+      .accounts({ escrow: pda, funder: funder.publicKey })
+      .rpc();
+    const before = await provider.connection.getBalance(payee.publicKey);
+    await program.methods.releaseFunds()
+      .accounts({ escrow: pda, recipient: payee.publicKey, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .signers([payee])
+      .rpc();
+    const after = await provider.connection.getBalance(payee.publicKey);
+    assert.approximately(after - before, 1_000_000_000, 10_000);
+
+    await program.methods.cancelEscrow()
+      .accounts({ escrow: pda, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    assert.equal(await provider.connection.getBalance(pda), rentFloor);
+  });
+
+  it("Reports the escrow balance in the funded event", async () => {
+    const payee = Keypair.generate();
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), payee.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeEscrow(milestones, new anchor.BN(deadline), false)
+      .accounts({ escrow: pda, funder: funder.publicKey, recipient: payee.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const sig = await program.methods
+      .fundEscrow(new anchor.BN(3_000_000_000))
+      .accounts({ escrow: pda, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    await provider.connection.confirmTransaction(sig, "confirmed");
+    const tx = await provider.connection.getTransaction(sig, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    const [ev] = Array.from(parser.parseLogs(tx.meta.logMessages));
+    assert.equal(ev.name, "EscrowFunded");
+    assert.equal(ev.data.totalFunded.toNumber(), 3_000_000_000);
+    assert.equal(ev.data.escrowBalance.toNumber(), await provider.connection.getBalance(pda, "confirmed"));
+  });
+
+  it("Scales percentage milestones with the funded amount", async () => {
+    const ms = [
+      { amount: new anchor.BN(0), description: null, installments: [], installmentsPaid: 0, payoutBps: 2500, fundingUnlockBps: 0 },
+      { amount: new anchor.BN(0), description: null, installments: [], installmentsPaid: 0, payoutBps: 7500, fundingUnlockBps: 0 },
+    ];
+    for (const funded of [1_000_000_000, 3_000_000_000]) {
+      const e = await setupMultiEscrow(ms, funded);
+      await approveAll(e, 0);
+      const before = await provider.connection.getBalance(e.payee.publicKey);
+      await program.methods
+        .releaseMilestoneFunds(0)
+        .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+        .signers([e.payee])
+        .rpc();
+      const after = await provider.connection.getBalance(e.payee.publicKey);
+      assert.approximately(after - before, funded / 4, 10_000);
+      const escrow = await program.account.escrow.fetch(e.pda);
+      assert.equal(escrow.totalReleased.toNumber(), funded / 4);
+    }
+
+    const payee = Keypair.generate();
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), funder.publicKey.toBuffer(), payee.publicKey.toBuffer()],
+      program.programId
+    );
+    const over = [{ ...ms[0], payoutBps: 5000 }, { ...ms[1], payoutBps: 6000 }];
+    await expect(
+      program.methods
+        .initializeEscrow(over, new anchor.BN(deadline), false)
+        .accounts({ escrow: pda, funder: funder.publicKey, recipient: payee.publicKey, systemProgram: SystemProgram.programId })
+        .rpc()
+    ).to.be.rejectedWith(/InvalidPayoutBps/);
+
+    // 100% in percentages leaves nothing for a fixed milestone
+    const fixedPlusAll = [...ms, { ...ms[0], amount: new anchor.BN(1_000_000_000), payoutBps: 0 }];
+    await expect(
+      program.methods
+        .initializeEscrow(fixedPlusAll, new anchor.BN(deadline), false)
+        .accounts({ escrow: pda, funder: funder.publicKey, recipient: payee.publicKey, systemProgram: SystemProgram.programId })
+        .rpc()
+    ).to.be.rejectedWith(/InvalidPayoutBps/);
+  });
+
+  it("Does not pay a milestone twice across both release paths", async () => {
+    const e = await setupMultiEscrow(milestones, 3_000_000_000);
+    await approveAll(e, 0);
+
+    await program.methods.releaseFunds()
+      .accounts({ escrow: e.pda, recipient: e.payee.publicKey, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .signers([e.payee])
+      .rpc();
+    const escrow = await program.account.escrow.fetch(e.pda);
+    assert.equal(escrow.releasedBitset, 1);
+    assert.equal(escrow.totalReleased.toNumber(), 1_000_000_000);
+
+    await expect(
+      program.methods
+        .releaseMilestoneFunds(0)
+        .accounts({ escrow: e.pda, milestoneApproval: approvalPda(e.pda, 0), recipient: e.payee.publicKey, funder: funder.publicKey, payeeHoldback: null, systemProgram: SystemProgram.programId })
+        .signers([e.payee])
+        .rpc()
+    ).to.be.rejectedWith(/MilestoneAlreadyReleased/);
+    await expect(
+      program.methods.releaseFunds()
+        .accounts({ escrow: e.pda, recipient: e.payee.publicKey, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+        .signers([e.payee])
+        .rpc()
+    ).to.be.rejectedWith(/NothingToRelease/);
+  });
+
+  it("Respects installment unlock times in release_funds", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const ms = [{
+      amount: new anchor.BN(900_000_000),
+      description: null,
+      installments: [
+        { unlockTime: new anchor.BN(now - 60), amount: new anchor.BN(400_000_000) },
+        { unlockTime: new anchor.BN(now + 86400), amount: new anchor.BN(500_000_000) },
+      ],
+      installmentsPaid: 0,
+      payoutBps: 0,
+      fundingUnlockBps: 0,
+    }];
+    const e = await setupMultiEscrow(ms, 900_000_000);
+    await approveAll(e, 0);
+    const releaseFunds = () => program.methods.releaseFunds()
+      .accounts({ escrow: e.pda, recipient: e.payee.publicKey, funder: funder.publicKey, systemProgram: SystemProgram.programId })
+      .signers([e.payee])
+      .rpc();
+
+    await releaseFunds();
+    const escrow = await program.account.escrow.fetch(e.pda);
+    assert.equal(escrow.totalReleased.toNumber(), 400_000_000);
+    assert.equal(escrow.milestones[0].installmentsPaid, 1);
+    assert.equal(escrow.releasedBitset, 0);
+
+    await expect(releaseFunds()).to.be.rejectedWith(/NothingToRelease/);
+  });
 });
\ No newline at end of file